@@ -2,3 +2,72 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+/// Number of top-ranked players reported as winners when a session settles
+#[constant]
+pub const SETTLEMENT_WINNERS: u8 = 3;
+
+/// Number of fills kept in each portfolio's trade history before the oldest is overwritten
+pub const TRADE_HISTORY_CAPACITY: usize = 32;
+
+/// Number of equity samples kept per portfolio before the oldest is overwritten
+pub const EQUITY_SAMPLE_CAPACITY: usize = 48;
+
+/// Minimum number of seconds between two equity samples
+#[constant]
+pub const EQUITY_SAMPLE_INTERVAL: i64 = 60;
+
+/// Upper bound on the session taker fee, in basis points
+#[constant]
+pub const MAX_TAKER_FEE_BPS: u16 = 1_000;
+
+/// Oldest oracle price, in seconds, an order may be filled at
+#[constant]
+pub const ORDER_PRICE_MAX_AGE: u64 = 30;
+
+/// Oldest oracle price, in seconds, a portfolio may be marked at
+#[constant]
+pub const MARK_PRICE_MAX_AGE: u64 = 60;
+
+/// Settlement prices must be published within this many seconds after `end_time`
+#[constant]
+pub const SETTLEMENT_PRICE_WINDOW: i64 = 10;
+
+/// Seconds after `end_time` from which the session authority may fix a settlement price the
+/// window missed
+#[constant]
+pub const SETTLEMENT_GRACE_PERIOD: i64 = 300;
+
+/// A circuit breaker reference older than this many seconds is replaced instead of compared,
+/// so ordinary drift on an idle pair does not halt it
+#[constant]
+pub const PRICE_GUARD_MAX_AGE: i64 = 60;
+
+/// Number of oracle samples kept per pair for TWAP valuation
+pub const TWAP_SAMPLE_CAPACITY: usize = 32;
+
+/// Length of the TWAP averaging window, in seconds
+#[constant]
+pub const TWAP_WINDOW: i64 = 300;
+
+/// Minimum number of seconds between the publish times of two TWAP samples, so a full buffer
+/// always spans at least the TWAP window
+#[constant]
+pub const TWAP_SAMPLE_INTERVAL: i64 = 10;
+
+/// Largest supported number of base-asset decimals for a trading pair
+#[constant]
+pub const MAX_BASE_DECIMALS: u8 = 12;
+
+/// Largest number of trading pairs a session may list
+pub const MAX_TRADING_PAIRS: usize = 20;
+
+/// Longest trading pair symbol, in bytes
+pub const MAX_SYMBOL_LEN: usize = 32;
+
+/// Most open positions a portfolio can hold; there is at most one position per pair
+pub const MAX_POSITIONS: usize = MAX_TRADING_PAIRS;
+
+/// Number of players a session leaderboard can rank; keeps the account under the 10 KiB CPI
+/// allocation limit so it can be created with `init`
+pub const MAX_LEADERBOARD_ENTRIES: usize = 128;
@@ -25,4 +25,78 @@ pub enum ErrorCode {
 
     #[msg("Unsupported trading pair")]
     UnsupportedTradingPair,
+    #[msg("Account does not belong to this session")]
+    SessionMismatch,
+    #[msg("Invalid number of portfolios in batch")]
+    InvalidBatchSize,
+    #[msg("Portfolio account must be writable")]
+    PortfolioNotWritable,
+    #[msg("Session has already been settled")]
+    SessionAlreadySettled,
+    #[msg("Every participant portfolio must be passed exactly once")]
+    IncompleteParticipants,
+    #[msg("Portfolio has not been marked to a price at or after session end")]
+    PortfolioNotMarked,
+    #[msg("Invalid fee or slippage configuration")]
+    InvalidFeeConfig,
+    #[msg("Invalid trading pair configuration")]
+    InvalidTradingPairConfig,
+    #[msg("Order quantity must be a positive multiple of the lot size")]
+    InvalidLotSize,
+    #[msg("Order notional is below the pair minimum")]
+    BelowMinNotional,
+    #[msg("Order amount is not valid for this order side")]
+    InvalidOrderAmount,
+    #[msg("Fill price moved beyond the order's price limit")]
+    PriceLimitExceeded,
+    #[msg("Position notional would exceed the session limit for a single pair")]
+    PositionLimitExceeded,
+    #[msg("Too many open positions")]
+    TooManyOpenPositions,
+    #[msg("Gross exposure would exceed the session limit")]
+    ExposureLimitExceeded,
+    #[msg("A session must list between 1 and MAX_TRADING_PAIRS trading pairs")]
+    TooManyTradingPairs,
+    #[msg("Trading pair symbol must be 1 to 32 bytes")]
+    InvalidSymbol,
+    #[msg("Trading pair is listed more than once")]
+    DuplicateTradingPair,
+    #[msg("Leaderboard is full")]
+    LeaderboardFull,
+    #[msg("Signer is not the session authority")]
+    Unauthorized,
+    #[msg("Account is not the oracle configured for this trading pair")]
+    OracleAccountMismatch,
+    #[msg("Trading pair is not priced manually")]
+    NotManualOracle,
+    #[msg("Mock oracle support is not compiled into this program")]
+    MockOracleDisabled,
+    #[msg("The same price account was passed more than once")]
+    DuplicatePriceAccount,
+    #[msg("Price update is not fully verified")]
+    InsufficientVerification,
+    #[msg("Settlement price has already been fixed")]
+    SettlementPriceAlreadyFixed,
+    #[msg("Settlement price must be published at or after session end")]
+    SettlementPriceTooEarly,
+    #[msg("Settlement price must be published within SETTLEMENT_PRICE_WINDOW of session end")]
+    SettlementPriceTooLate,
+    #[msg("Settlement price has not been fixed")]
+    SettlementPriceNotFixed,
+    #[msg("Trading on this pair is halted by the circuit breaker")]
+    TradingHalted,
+    #[msg("Session is not configured to delegate its leaderboard")]
+    LeaderboardDelegationDisabled,
+    #[msg("Account is still delegated to the ephemeral rollup; undelegate it first")]
+    AccountDelegated,
+    #[msg("Account is delegated; send this instruction to the ephemeral rollup")]
+    RequiresEphemeralRollup,
+    #[msg("Session end time must be in the future")]
+    InvalidEndTime,
+    #[msg("Session has reached MAX_LEADERBOARD_ENTRIES participants")]
+    SessionFull,
+    #[msg("Signer does not own this portfolio")]
+    NotPortfolioOwner,
+    #[msg("Settlement prices can only be overridden SETTLEMENT_GRACE_PERIOD after session end")]
+    SettlementGracePeriodActive,
 }
@@ -1,13 +1,61 @@
 use anchor_lang::prelude::*;
 
-use crate::OrderSide;
+use crate::{ErConfig, FeeConfig, OrderSide, RiskLimits, TradingPair, ValuationConfig};
 // Events
 // ----------------------------
+// `version` is the version of the account the event reports on, after the change: the
+// portfolio's for trading and PnL events, the leaderboard's for ranking events and the
+// session's `config_version` for session config and results, and the pair's price guard
+// for circuit breaker events. Indexers order events by it and can detect gaps.
+
+/// Carries the full initial config, so the session can be rebuilt from events alone
 #[event]
 pub struct SessionInitialized {
     pub session_id: u64,
+    pub authority: Pubkey,
     pub start_time: i64,
     pub end_time: i64,
+    pub virtual_balance: u64,
+    pub trading_pairs: Vec<TradingPair>,
+    pub fees: FeeConfig,
+    pub risk_limits: RiskLimits,
+    pub valuation: ValuationConfig,
+    pub er_config: ErConfig,
+    pub version: u64,
+}
+
+#[event]
+pub struct TradingPairsAdded {
+    pub session_id: u64,
+    /// Pair index of the first new pair; the rest follow in order
+    pub first_pair_index: u16,
+    pub trading_pairs: Vec<TradingPair>,
+    pub version: u64,
+}
+
+#[event]
+pub struct ManualPriceSet {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub price: i64,
+    pub updated_at: i64,
+    pub version: u64,
+}
+
+#[event]
+pub struct MockPriceSet {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub price: i64,
+    pub publish_time: i64,
+}
+
+#[event]
+pub struct PriceSampled {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub price: i64,
+    pub publish_time: i64,
 }
 
 #[event]
@@ -15,35 +63,162 @@ pub struct ParticipantJoined {
     pub session_id: u64,
     pub user: Pubkey,
     pub initial_balance: u64,
+    /// Session participant count including this player
+    pub participant_count: u32,
+    pub version: u64,
 }
 
 #[event]
 pub struct OrderExecuted {
+    pub session_id: u64,
     pub user: Pubkey,
-    pub trading_pair: String,
+    /// Index into the session's `trading_pairs`
+    pub pair_index: u16,
     pub side: OrderSide,
     pub quantity: u64,
+    /// Fill price after slippage
     pub price: i64,
+    pub oracle_price: i64,
+    pub slippage_bps: u16,
+    pub fee: u64,
+    /// Realized PnL of the fill, net of `fee`
+    pub realized_pnl: i64,
+    /// Position in the pair after the fill
+    pub position_quantity: u64,
+    pub avg_entry_price: i64,
+    pub cash_balance: i64,
+    pub total_value: i64,
+    pub timestamp: i64,
+    pub version: u64,
+}
+
+#[event]
+pub struct PositionOpened {
+    pub session_id: u64,
+    pub user: Pubkey,
+    pub pair_index: u16,
+    pub quantity: u64,
+    pub entry_price: i64,
     pub timestamp: i64,
+    pub version: u64,
+}
+
+#[event]
+pub struct PositionClosed {
+    pub session_id: u64,
+    pub user: Pubkey,
+    pub pair_index: u16,
+    /// Quantity sold by the closing fill
+    pub quantity: u64,
+    pub exit_price: i64,
+    /// Realized PnL of the closing fill, net of its fee
+    pub realized_pnl: i64,
+    pub timestamp: i64,
+    pub version: u64,
+}
+
+#[event]
+pub struct TradingHalted {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub last_price: i64,
+    pub price: i64,
+    pub move_bps: i64,
+    pub version: u64,
+}
+
+#[event]
+pub struct TradingResumed {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub version: u64,
 }
 
 #[event]
 pub struct PnlUpdated {
+    pub session_id: u64,
     pub user: Pubkey,
     pub unrealized_pnl: i64,
     pub realized_pnl: i64,
     pub total_value: i64,
+    pub cash_balance: i64,
+    pub max_drawdown_bps: u32,
+    /// Oldest oracle publish time used in the valuation
+    pub mark_time: i64,
+    pub version: u64,
 }
 
 #[event]
 pub struct LeaderboardUpdated {
     pub session_id: u64,
+    pub num_entries: u32,
+    pub version: u64,
+}
+
+/// Ranks moved during a re-rank, as one event per re-rank so a new leader shifting the whole
+/// board stays well under the log limit. `previous_ranks[i]` is the previous rank of the entry
+/// now at `first_rank + i`, covering the span from the first to the last moved entry; 0 marks a
+/// newly ranked player, whose keys are listed in `new_users` in rank order.
+#[event]
+pub struct RanksChanged {
+    pub session_id: u64,
+    pub first_rank: u32,
+    pub previous_ranks: Vec<u16>,
+    pub new_users: Vec<Pubkey>,
+    pub version: u64,
 }
 
 #[event]
 pub struct SessionClosed {
     pub session_id: u64,
     pub participant_count: u32,
+    pub closed_at: i64,
+    /// Top `SETTLEMENT_WINNERS` of the leaderboard at close
+    pub leaders: Vec<LeaderboardResult>,
+    pub leaderboard_version: u64,
+    pub version: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LeaderboardResult {
+    pub user: Pubkey,
+    pub rank: u32,
+    pub total_pnl: i64,
+    pub roi_percentage: f64,
+}
+
+#[event]
+pub struct SettlementPriceFixed {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub price: i64,
+    pub publish_time: i64,
+    /// Fixed by the authority from a later spot price after the grace period
+    pub authority_override: bool,
+    pub version: u64,
+}
+
+#[event]
+pub struct SessionSettled {
+    pub session_id: u64,
+    pub participant_count: u32,
+    pub winners: Vec<Pubkey>,
+    pub settled_at: i64,
+    pub version: u64,
+}
+
+/// Final result of one player, emitted by `settle_portfolios`. Ranks are assigned when
+/// `settle_session` seals the standings: by `total_pnl` descending, ties in settlement order.
+#[event]
+pub struct PlayerSettled {
+    pub session_id: u64,
+    pub user: Pubkey,
+    pub total_value: i64,
+    pub total_pnl: i64,
+    pub roi_percentage: f64,
+    pub num_trades: u32,
+    pub max_drawdown_bps: u32,
+    pub version: u64,
 }
 
 #[event]
@@ -52,12 +227,57 @@ pub struct AccountsDelegated {
     pub session_id: u64,
 }
 #[event]
+pub struct LeaderboardDelegated {
+    pub session_id: u64,
+    pub validator: Option<Pubkey>,
+}
+#[event]
 pub struct AccountsCheckpointed {
     pub session_id: u64,
     pub user: Pubkey,
+    /// Committed portfolio version; `None` when the portfolio was unchanged and skipped
+    pub portfolio_version: Option<u64>,
+    /// Committed leaderboard version; `None` when it was unchanged or not delegated
+    pub leaderboard_version: Option<u64>,
 }
 #[event]
 pub struct AccountsFinalized {
     pub session_id: u64,
     pub user: Pubkey,
+    pub portfolio_version: u64,
+    /// `None` when the session does not delegate its leaderboard
+    pub leaderboard_version: Option<u64>,
+}
+#[event]
+pub struct SessionAccountsFinalized {
+    pub session_id: u64,
+    pub portfolio_count: u8,
+    /// Whether the leaderboard was undelegated in this batch
+    pub leaderboard: bool,
+    /// Whether the session mirror was undelegated in this batch
+    pub session_mirror: bool,
+}
+#[event]
+pub struct SessionConfigUpdated {
+    pub session_id: u64,
+    pub end_time: i64,
+    pub fees: FeeConfig,
+    pub risk_limits: RiskLimits,
+    pub config_version: u64,
+}
+#[event]
+pub struct SessionMirrorSynced {
+    pub session_id: u64,
+    pub config_version: u64,
+}
+#[event]
+pub struct SessionMirrorDelegated {
+    pub session_id: u64,
+    pub validator: Option<Pubkey>,
+}
+#[event]
+pub struct PriceGuardDelegated {
+    pub session_id: u64,
+    pub pair_index: u16,
+    pub validator: Option<Pubkey>,
 }
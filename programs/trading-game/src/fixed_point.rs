@@ -0,0 +1,398 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Decimals used for every price and quote amount (1_000_000 = 1.0 USD)
+pub const QUOTE_DECIMALS: u32 = 6;
+pub const BPS_DENOMINATOR: i128 = 10_000;
+
+/// How to round when a division does not come out even
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards negative infinity
+    Down,
+    /// Towards positive infinity
+    Up,
+}
+
+/// Quote-currency amount with `QUOTE_DECIMALS` decimals (cash, notional, PnL, fees)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Money(i64);
+
+/// Quote per one whole unit of the base asset, with `QUOTE_DECIMALS` decimals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Price(i64);
+
+/// Base-asset amount in the pair's smallest unit (`10^-base_decimals`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Quantity(u64);
+
+fn div_round(numerator: i128, denominator: i128, rounding: Rounding) -> Result<i128> {
+    require!(denominator != 0, ErrorCode::MathOverflow);
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder == 0 {
+        return Ok(quotient);
+    }
+    // `/` truncates towards zero; adjust when that is the wrong direction
+    let exact_is_negative = (remainder < 0) != (denominator < 0);
+    Ok(match (rounding, exact_is_negative) {
+        (Rounding::Down, true) => quotient - 1,
+        (Rounding::Up, false) => quotient + 1,
+        _ => quotient,
+    })
+}
+
+fn to_i64(value: i128) -> Result<i64> {
+    i64::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+fn to_u64(value: i128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn new(raw: i64) -> Self {
+        Money(raw)
+    }
+
+    pub fn from_unsigned(raw: u64) -> Result<Self> {
+        Ok(Money(
+            i64::try_from(raw).map_err(|_| ErrorCode::MathOverflow)?,
+        ))
+    }
+
+    pub fn raw(self) -> i64 {
+        self.0
+    }
+
+    pub fn to_unsigned(self) -> Result<u64> {
+        to_u64(self.0 as i128)
+    }
+
+    /// `quantity * price`, converted from base units to quote units
+    pub fn notional(
+        quantity: Quantity,
+        price: Price,
+        base_unit: i64,
+        rounding: Rounding,
+    ) -> Result<Self> {
+        let scaled = (quantity.0 as i128)
+            .checked_mul(price.0 as i128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(Money(to_i64(div_round(
+            scaled,
+            base_unit as i128,
+            rounding,
+        )?)?))
+    }
+
+    /// `self * bps / 10_000`
+    pub fn mul_bps(self, bps: u16, rounding: Rounding) -> Result<Self> {
+        let scaled = (self.0 as i128)
+            .checked_mul(bps as i128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(Money(to_i64(div_round(
+            scaled,
+            BPS_DENOMINATOR,
+            rounding,
+        )?)?))
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Self> {
+        Ok(Money(
+            self.0.checked_add(other.0).ok_or(ErrorCode::MathOverflow)?,
+        ))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Self> {
+        Ok(Money(
+            self.0.checked_sub(other.0).ok_or(ErrorCode::MathOverflow)?,
+        ))
+    }
+
+    pub fn checked_neg(self) -> Result<Self> {
+        Ok(Money(self.0.checked_neg().ok_or(ErrorCode::MathOverflow)?))
+    }
+
+    /// `self / other` in basis points, e.g. a return on `other`
+    pub fn ratio_bps(self, other: Money, rounding: Rounding) -> Result<i64> {
+        let scaled = (self.0 as i128)
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(ErrorCode::MathOverflow)?;
+        to_i64(div_round(scaled, other.0 as i128, rounding)?)
+    }
+}
+
+impl Price {
+    pub fn new(raw: i64) -> Self {
+        Price(raw)
+    }
+
+    pub fn raw(self) -> i64 {
+        self.0
+    }
+
+    /// Rescale an oracle `price * 10^exponent` to `QUOTE_DECIMALS` decimals
+    pub fn from_oracle(price: impl Into<i128>, exponent: i32, rounding: Rounding) -> Result<Self> {
+        let price: i128 = price.into();
+        let shift = exponent
+            .checked_add(QUOTE_DECIMALS as i32)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let factor = 10_i128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(ErrorCode::MathOverflow)?;
+        let normalized = if shift >= 0 {
+            price
+                .checked_mul(factor)
+                .ok_or(ErrorCode::MathOverflow)?
+        } else {
+            div_round(price, factor, rounding)?
+        };
+        Ok(Price(to_i64(normalized)?))
+    }
+
+    /// Scale the price by `(10_000 + bps) / 10_000`
+    pub fn adjust_bps(self, bps: i32, rounding: Rounding) -> Result<Self> {
+        let factor = BPS_DENOMINATOR
+            .checked_add(bps as i128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let scaled = (self.0 as i128)
+            .checked_mul(factor)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(Price(to_i64(div_round(
+            scaled,
+            BPS_DENOMINATOR,
+            rounding,
+        )?)?))
+    }
+
+    /// Distance from `reference` in basis points of `reference`
+    pub fn deviation_bps(self, reference: Price, rounding: Rounding) -> Result<i64> {
+        let distance = (self.0 as i128)
+            .checked_sub(reference.0 as i128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .abs();
+        let scaled = distance
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(ErrorCode::MathOverflow)?;
+        to_i64(div_round(scaled, reference.0 as i128, rounding)?)
+    }
+
+    /// Quantity-weighted average of two prices, e.g. when adding to a position
+    pub fn weighted_average(
+        quantity_a: Quantity,
+        price_a: Price,
+        quantity_b: Quantity,
+        price_b: Price,
+        rounding: Rounding,
+    ) -> Result<Self> {
+        let total_quantity = quantity_a.checked_add(quantity_b)?;
+        let total_cost = (quantity_a.0 as i128)
+            .checked_mul(price_a.0 as i128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_add(
+                (quantity_b.0 as i128)
+                    .checked_mul(price_b.0 as i128)
+                    .ok_or(ErrorCode::MathOverflow)?,
+            )
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(Price(to_i64(div_round(
+            total_cost,
+            total_quantity.0 as i128,
+            rounding,
+        )?)?))
+    }
+
+    /// Average of prices weighted by how long each was in effect, in seconds
+    pub fn time_weighted_average(points: &[(Price, i64)], rounding: Rounding) -> Result<Self> {
+        let mut total_weight: i128 = 0;
+        let mut weighted_sum: i128 = 0;
+        for (price, duration) in points {
+            total_weight = total_weight
+                .checked_add(*duration as i128)
+                .ok_or(ErrorCode::MathOverflow)?;
+            weighted_sum = (price.0 as i128)
+                .checked_mul(*duration as i128)
+                .and_then(|weighted| weighted_sum.checked_add(weighted))
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        Ok(Price(to_i64(div_round(
+            weighted_sum,
+            total_weight,
+            rounding,
+        )?)?))
+    }
+}
+
+impl Quantity {
+    pub const ZERO: Quantity = Quantity(0);
+
+    pub fn new(raw: u64) -> Self {
+        Quantity(raw)
+    }
+
+    pub fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Base units that `notional` buys at `price`
+    pub fn from_notional(
+        notional: Money,
+        price: Price,
+        base_unit: i64,
+        rounding: Rounding,
+    ) -> Result<Self> {
+        require!(price.0 > 0, ErrorCode::InvalidPriceData);
+        let scaled = (notional.0 as i128)
+            .checked_mul(base_unit as i128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(Quantity(to_u64(div_round(
+            scaled,
+            price.0 as i128,
+            rounding,
+        )?)?))
+    }
+
+    pub fn checked_add(self, other: Quantity) -> Result<Self> {
+        Ok(Quantity(
+            self.0.checked_add(other.0).ok_or(ErrorCode::MathOverflow)?,
+        ))
+    }
+
+    pub fn checked_sub(self, other: Quantity) -> Result<Self> {
+        Ok(Quantity(
+            self.0.checked_sub(other.0).ok_or(ErrorCode::MathOverflow)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overflow() -> anchor_lang::error::Error {
+        ErrorCode::MathOverflow.into()
+    }
+
+    #[test]
+    fn div_round_rounds_in_the_requested_direction() {
+        assert_eq!(div_round(7, 2, Rounding::Down).unwrap(), 3);
+        assert_eq!(div_round(7, 2, Rounding::Up).unwrap(), 4);
+        assert_eq!(div_round(6, 2, Rounding::Up).unwrap(), 3);
+    }
+
+    #[test]
+    fn div_round_with_negative_operands() {
+        // -3.5 rounds down to -4 and up to -3, whichever operand carries the sign
+        assert_eq!(div_round(-7, 2, Rounding::Down).unwrap(), -4);
+        assert_eq!(div_round(-7, 2, Rounding::Up).unwrap(), -3);
+        assert_eq!(div_round(7, -2, Rounding::Down).unwrap(), -4);
+        assert_eq!(div_round(7, -2, Rounding::Up).unwrap(), -3);
+        // both negative gives +3.5
+        assert_eq!(div_round(-7, -2, Rounding::Down).unwrap(), 3);
+        assert_eq!(div_round(-7, -2, Rounding::Up).unwrap(), 4);
+        assert_eq!(div_round(-6, 2, Rounding::Down).unwrap(), -3);
+    }
+
+    #[test]
+    fn div_round_rejects_zero_denominator() {
+        assert_eq!(div_round(1, 0, Rounding::Down).unwrap_err(), overflow());
+    }
+
+    #[test]
+    fn from_oracle_with_negative_exponent() {
+        // Pyth-style 8 decimals: 123.45678901 -> 123.456789 / 123.456790
+        let price = Price::from_oracle(12_345_678_901_i64, -8, Rounding::Down).unwrap();
+        assert_eq!(price.raw(), 123_456_789);
+        let price = Price::from_oracle(12_345_678_901_i64, -8, Rounding::Up).unwrap();
+        assert_eq!(price.raw(), 123_456_790);
+        // already 6 decimals
+        assert_eq!(Price::from_oracle(1_500_000_i64, -6, Rounding::Down).unwrap().raw(), 1_500_000);
+    }
+
+    #[test]
+    fn from_oracle_with_positive_exponent() {
+        assert_eq!(Price::from_oracle(15_i64, 2, Rounding::Down).unwrap().raw(), 1_500_000_000);
+        assert_eq!(Price::from_oracle(3_i64, 0, Rounding::Down).unwrap().raw(), 3_000_000);
+    }
+
+    #[test]
+    fn from_oracle_overflow() {
+        assert_eq!(Price::from_oracle(i64::MAX, 0, Rounding::Down).unwrap_err(), overflow());
+        assert_eq!(Price::from_oracle(1_i64, 40, Rounding::Down).unwrap_err(), overflow());
+        assert_eq!(Price::from_oracle(1_i64, i32::MAX, Rounding::Down).unwrap_err(), overflow());
+    }
+
+    #[test]
+    fn checked_arithmetic_overflows() {
+        assert_eq!(Money::new(i64::MAX).checked_add(Money::new(1)).unwrap_err(), overflow());
+        assert_eq!(Money::new(i64::MIN).checked_sub(Money::new(1)).unwrap_err(), overflow());
+        assert_eq!(Money::new(i64::MIN).checked_neg().unwrap_err(), overflow());
+        assert_eq!(Money::new(-1).to_unsigned().unwrap_err(), overflow());
+        assert_eq!(Money::from_unsigned(u64::MAX).unwrap_err(), overflow());
+        assert_eq!(Quantity::ZERO.checked_sub(Quantity::new(1)).unwrap_err(), overflow());
+        assert_eq!(
+            Money::notional(Quantity::new(u64::MAX), Price::new(i64::MAX), 1, Rounding::Down)
+                .unwrap_err(),
+            overflow()
+        );
+    }
+
+    #[test]
+    fn notional_rounds_per_leg() {
+        // 1.5 units of a 9-decimal asset at 0.333333
+        let quantity = Quantity::new(1_500_000_000);
+        let price = Price::new(333_333);
+        let base_unit = 1_000_000_000;
+        let down = Money::notional(quantity, price, base_unit, Rounding::Down).unwrap();
+        let up = Money::notional(quantity, price, base_unit, Rounding::Up).unwrap();
+        assert_eq!(down.raw(), 499_999);
+        assert_eq!(up.raw(), 500_000);
+    }
+
+    #[test]
+    fn weighted_average_of_two_fills() {
+        // 1 @ 100 and 3 @ 200 average to 175
+        let average = Price::weighted_average(
+            Quantity::new(1),
+            Price::new(100_000_000),
+            Quantity::new(3),
+            Price::new(200_000_000),
+            Rounding::Up,
+        )
+        .unwrap();
+        assert_eq!(average.raw(), 175_000_000);
+
+        // 1 @ 1 and 2 @ 2 average to 1.666666..., rounded in the requested direction
+        let (a, b) = (Price::new(1_000_000), Price::new(2_000_000));
+        let down =
+            Price::weighted_average(Quantity::new(1), a, Quantity::new(2), b, Rounding::Down);
+        let up = Price::weighted_average(Quantity::new(1), a, Quantity::new(2), b, Rounding::Up);
+        assert_eq!(down.unwrap().raw(), 1_666_666);
+        assert_eq!(up.unwrap().raw(), 1_666_667);
+    }
+
+    #[test]
+    fn weighted_average_overflow() {
+        let result = Price::weighted_average(
+            Quantity::new(u64::MAX),
+            Price::new(1),
+            Quantity::new(1),
+            Price::new(1),
+            Rounding::Down,
+        );
+        assert_eq!(result.unwrap_err(), overflow());
+        // zero total quantity has no average
+        let result = Price::weighted_average(
+            Quantity::ZERO,
+            Price::new(1),
+            Quantity::ZERO,
+            Price::new(1),
+            Rounding::Down,
+        );
+        assert_eq!(result.unwrap_err(), overflow());
+    }
+}
@@ -1,82 +1,81 @@
 use anchor_lang::prelude::*;
-use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, get_feed_id_from_hex};
-use crate::{error::ErrorCode, Portfolio, Position};
+use crate::{
+    check_price_accounts, error::ErrorCode, read_mark_price, track_equity, Money, OraclePrice,
+    Portfolio, Price, Quantity, Rounding, TradingSession, MARK_PRICE_MAX_AGE,
+};
 
 pub fn calculate_portfolio_value(
     portfolio: &mut Portfolio,
+    session: &TradingSession,
+    session_key: &Pubkey,
     price_update_accounts: &[AccountInfo],
 ) -> Result<()> {
-    let mut unrealized_pnl: i64 = 0;
+    let mut unrealized_pnl = Money::ZERO;
+    let mut market_value = Money::ZERO;
     let clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp;
+    // The portfolio is only as fresh as its oldest mark; a flat portfolio is marked "now"
+    let mut mark_time = current_timestamp;
+    check_price_accounts(price_update_accounts)?;
+    // once the session has ended every player is valued at the same fixed prices
+    let settling = current_timestamp >= session.end_time;
 
-    for position in &portfolio.positions {
-        // Get the Pyth price feed for this trading pair
-        let price = get_price_for_pair(
-            &position.trading_pair,
-            price_update_accounts,
-            current_timestamp,
-        )?;
-
-        // Calculate position value with oracle price
-        let current_value = (position.quantity as i64)
-            .checked_mul(price)
-            .ok_or(ErrorCode::MathOverflow)?
-            / 1_000_000;
+    // positions[i] belongs to session.trading_pairs[i]
+    for (pair_index, (pair, position)) in session
+        .trading_pairs
+        .iter()
+        .zip(portfolio.positions.iter())
+        .enumerate()
+        .filter(|(_, (_, position))| position.quantity > 0)
+    {
+        // Read the pair's price in the session's valuation mode
+        let oracle_price = if settling {
+            let fixed = &session.settlement_prices[pair_index];
+            if !fixed.is_fixed() {
+                msg!("Settlement price for {} is not fixed yet", pair.symbol);
+                return Err(ErrorCode::SettlementPriceNotFixed.into());
+            }
+            OraclePrice {
+                price: Price::new(fixed.price),
+                publish_time: fixed.publish_time,
+            }
+        } else {
+            read_mark_price(
+                pair,
+                pair_index as u16,
+                session.valuation.mark,
+                session_key,
+                price_update_accounts,
+                &clock,
+                MARK_PRICE_MAX_AGE,
+            )?
+        };
+        let price = oracle_price.price;
+        mark_time = mark_time.min(oracle_price.publish_time);
+        let base_unit = pair.base_unit();
+        let quantity = Quantity::new(position.quantity);
 
-        let cost_basis = (position.quantity as i64)
-            .checked_mul(position.avg_entry_price)
-            .ok_or(ErrorCode::MathOverflow)?
-            / 1_000_000;
+        // Calculate position value with oracle price; round down so marks are conservative
+        let current_value = Money::notional(quantity, price, base_unit, Rounding::Down)?;
+        let cost_basis = Money::notional(
+            quantity,
+            Price::new(position.avg_entry_price),
+            base_unit,
+            Rounding::Down,
+        )?;
 
-        unrealized_pnl = unrealized_pnl
-            .checked_add(
-                current_value
-                    .checked_sub(cost_basis)
-                    .ok_or(ErrorCode::MathOverflow)?
-            )
-            .ok_or(ErrorCode::MathOverflow)?;
+        unrealized_pnl = unrealized_pnl.checked_add(current_value.checked_sub(cost_basis)?)?;
+        market_value = market_value.checked_add(current_value)?;
     }
 
-    portfolio.unrealized_pnl = unrealized_pnl;
-    portfolio.total_value = portfolio
-        .cash_balance
-        .checked_add(portfolio.realized_pnl)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_add(unrealized_pnl)
-        .ok_or(ErrorCode::MathOverflow)?;
+    portfolio.unrealized_pnl = unrealized_pnl.raw();
+    portfolio.last_mark_time = mark_time;
+    // Realized PnL is already reflected in cash, so equity is cash plus marked positions
+    portfolio.total_value = Money::new(portfolio.cash_balance)
+        .checked_add(market_value)?
+        .raw();
+
+    track_equity(portfolio, current_timestamp)?;
 
     Ok(())
 }
-
-fn get_price_for_pair(
-    trading_pair: &str,
-    price_accounts: &[AccountInfo],
-    current_timestamp: i64,
-) -> Result<i64> {
-    // Map your trading pairs to Pyth feed IDs
-    let feed_id = match trading_pair {
-        "JUP/USD" => get_feed_id_from_hex("0x0a0408d619e9380abad35060f9192039ed5042fa6f82301d0e48bb52be830996")?,
-        "SOL/USD" => get_feed_id_from_hex("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d")?,
-        _ => return Err(ErrorCode::UnsupportedTradingPair.into()),
-    };
-
-    // Find the matching price account
-    for account_info in price_accounts {
-        let price_update = PriceUpdateV2::try_deserialize(&mut &account_info.data.borrow()[..])?;
-        
-        if price_update.price_message.feed_id == feed_id {
-            // Check price is not stale (e.g., within last 60 seconds)
-            let price_age = current_timestamp
-                .checked_sub(price_update.price_message.publish_time)
-                .ok_or(ErrorCode::MathOverflow)?;
-            
-            require!(price_age <= 60, ErrorCode::StalePriceData);
-
-            // Return price scaled to your 6 decimal format
-            return Ok(price_update.price_message.price);
-        }
-    }
-
-    Err(ErrorCode::PriceFeedNotFound.into())
-}
\ No newline at end of file
@@ -1,13 +1,14 @@
+use crate::{Money, Portfolio, Rounding};
 use anchor_lang::prelude::*;
-use crate::Portfolio;
 
 pub fn calculate_roi(portfolio: &Portfolio) -> Result<f64> {
-    // initial balance can be taken from session or constant; using a constant here for example
-    let initial_balance: f64 = 100_000.0; // human-readable dollars
-    let total_pnl = (portfolio.realized_pnl + portfolio.unrealized_pnl) as f64 / 1_000_000.0;
+    let initial_balance = Money::from_unsigned(portfolio.initial_balance)?;
     // avoid divide by zero
-    if initial_balance.abs() < f64::EPSILON {
+    if initial_balance == Money::ZERO {
         return Ok(0.0);
     }
-    Ok((total_pnl / initial_balance) * 100.0)
-}
\ No newline at end of file
+    let total_pnl =
+        Money::new(portfolio.realized_pnl).checked_add(Money::new(portfolio.unrealized_pnl))?;
+    let roi_bps = total_pnl.ratio_bps(initial_balance, Rounding::Down)?;
+    Ok(roi_bps as f64 / 100.0)
+}
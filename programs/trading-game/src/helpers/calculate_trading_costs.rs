@@ -0,0 +1,30 @@
+use crate::{FeeConfig, Money, OrderSide, Price, Rounding};
+use anchor_lang::prelude::*;
+
+/// Slippage grows linearly with order notional in steps of `slippage_step_notional`,
+/// capped at `max_slippage_bps`.
+pub fn calculate_slippage_bps(fees: &FeeConfig, notional: Money) -> u16 {
+    if fees.slippage_step_notional == 0 || notional <= Money::ZERO {
+        return 0;
+    }
+    let steps = notional.raw() as u64 / fees.slippage_step_notional;
+    let slippage_bps = steps.saturating_mul(fees.slippage_bps_per_step as u64);
+    slippage_bps.min(fees.max_slippage_bps as u64) as u16
+}
+
+/// Move the oracle price against the taker: buys fill higher, sells fill lower.
+pub fn calculate_fill_price(
+    oracle_price: Price,
+    side: &OrderSide,
+    slippage_bps: u16,
+) -> Result<Price> {
+    match side {
+        OrderSide::Buy => oracle_price.adjust_bps(slippage_bps as i32, Rounding::Up),
+        OrderSide::Sell => oracle_price.adjust_bps(-(slippage_bps as i32), Rounding::Down),
+    }
+}
+
+/// Taker fee on the filled notional, rounded up so fees can't be dodged by splitting orders.
+pub fn calculate_taker_fee(fees: &FeeConfig, notional: Money) -> Result<Money> {
+    notional.mul_bps(fees.taker_fee_bps, Rounding::Up)
+}
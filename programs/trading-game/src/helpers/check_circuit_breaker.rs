@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, Price, PriceGuard, Rounding, PRICE_GUARD_MAX_AGE};
+
+/// Fail while the pair is halted; otherwise return the move in basis points when `price`,
+/// published at `publish_time`, moved further from the last traded price than
+/// `max_price_move_bps` allows. A reference older than `PRICE_GUARD_MAX_AGE` is not compared.
+pub fn check_circuit_breaker(
+    guard: &PriceGuard,
+    max_price_move_bps: u16,
+    price: Price,
+    publish_time: i64,
+) -> Result<Option<i64>> {
+    require!(!guard.is_halted(), ErrorCode::TradingHalted);
+    if max_price_move_bps == 0
+        || guard.last_price == 0
+        || publish_time.saturating_sub(guard.last_publish_time) > PRICE_GUARD_MAX_AGE
+    {
+        return Ok(None);
+    }
+
+    // round up so a move just over the threshold is never rounded back under it
+    let move_bps = price.deviation_bps(Price::new(guard.last_price), Rounding::Up)?;
+    Ok((move_bps > max_price_move_bps as i64).then_some(move_bps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    const USD: i64 = 1_000_000;
+    const MAX_MOVE_BPS: u16 = 500;
+
+    fn guard(last_price: i64, last_publish_time: i64) -> PriceGuard {
+        let mut guard = PriceGuard::zeroed();
+        guard.last_price = last_price;
+        guard.last_publish_time = last_publish_time;
+        guard
+    }
+
+    fn check(guard: &PriceGuard, price: i64, publish_time: i64) -> Option<i64> {
+        check_circuit_breaker(guard, MAX_MOVE_BPS, Price::new(price), publish_time).unwrap()
+    }
+
+    #[test]
+    fn move_of_exactly_the_threshold_is_allowed() {
+        let guard = guard(100 * USD, 1_000);
+        assert_eq!(check(&guard, 105 * USD, 1_010), None);
+        assert_eq!(check(&guard, 95 * USD, 1_010), None);
+    }
+
+    #[test]
+    fn move_just_over_the_threshold_trips_rounded_up() {
+        let guard = guard(100 * USD, 1_000);
+        assert_eq!(check(&guard, 105 * USD + 1, 1_010), Some(501));
+        assert_eq!(check(&guard, 95 * USD - 1, 1_010), Some(501));
+    }
+
+    #[test]
+    fn reference_is_compared_up_to_the_maximum_age() {
+        let guard = guard(100 * USD, 1_000);
+        assert_eq!(check(&guard, 200 * USD, 1_000 + PRICE_GUARD_MAX_AGE), Some(10_000));
+    }
+
+    #[test]
+    fn aged_reference_is_not_compared() {
+        let guard = guard(100 * USD, 1_000);
+        assert_eq!(check(&guard, 200 * USD, 1_001 + PRICE_GUARD_MAX_AGE), None);
+    }
+
+    #[test]
+    fn missing_reference_or_disabled_breaker_never_trips() {
+        assert_eq!(check(&guard(0, 0), 200 * USD, 10), None);
+        let guard = guard(100 * USD, 1_000);
+        assert_eq!(
+            check_circuit_breaker(&guard, 0, Price::new(200 * USD), 1_010).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn halted_pair_rejects_every_order() {
+        let mut guard = guard(0, 0);
+        guard.halted = 1;
+        assert_eq!(
+            check_circuit_breaker(&guard, 0, Price::new(100 * USD), 1_000).unwrap_err(),
+            ErrorCode::TradingHalted.into()
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, OrderSide, Price, PriceProtection, Rounding};
+
+/// Abort if the fill price is worse for the taker than the order allows:
+/// above the bound for buys, below it for sells.
+pub fn check_price_protection(
+    protection: &Option<PriceProtection>,
+    side: &OrderSide,
+    fill_price: Price,
+) -> Result<()> {
+    let bound = match protection {
+        None => return Ok(()),
+        Some(PriceProtection::Limit(limit_price)) => Price::new(*limit_price),
+        Some(PriceProtection::Tolerance {
+            reference_price,
+            max_deviation_bps,
+        }) => {
+            let reference_price = Price::new(*reference_price);
+            match side {
+                OrderSide::Buy => {
+                    reference_price.adjust_bps(*max_deviation_bps as i32, Rounding::Down)?
+                }
+                OrderSide::Sell => {
+                    reference_price.adjust_bps(-(*max_deviation_bps as i32), Rounding::Up)?
+                }
+            }
+        }
+    };
+
+    let within_bound = match side {
+        OrderSide::Buy => fill_price <= bound,
+        OrderSide::Sell => fill_price >= bound,
+    };
+    require!(within_bound, ErrorCode::PriceLimitExceeded);
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, Money, Portfolio, RiskLimits, MAX_POSITIONS};
+
+/// Enforce session risk limits after a position-increasing fill has been applied and the
+/// portfolio revalued. `position_notional` is the notional of the traded pair's whole position.
+pub fn check_risk_limits(
+    limits: &RiskLimits,
+    portfolio: &Portfolio,
+    position_notional: Money,
+) -> Result<()> {
+    let max_open_positions = match limits.max_open_positions {
+        0 => MAX_POSITIONS,
+        max => (max as usize).min(MAX_POSITIONS),
+    };
+    require!(
+        portfolio.open_positions() <= max_open_positions,
+        ErrorCode::TooManyOpenPositions
+    );
+
+    if limits.max_position_notional > 0 {
+        require!(
+            position_notional <= Money::from_unsigned(limits.max_position_notional)?,
+            ErrorCode::PositionLimitExceeded
+        );
+    }
+
+    if limits.max_gross_exposure > 0 {
+        // positions are long-only, so gross exposure is the marked value of all positions
+        let gross_exposure =
+            Money::new(portfolio.total_value).checked_sub(Money::new(portfolio.cash_balance))?;
+        require!(
+            gross_exposure <= Money::from_unsigned(limits.max_gross_exposure)?,
+            ErrorCode::ExposureLimitExceeded
+        );
+    }
+
+    Ok(())
+}
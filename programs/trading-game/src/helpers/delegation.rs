@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::consts::DELEGATION_PROGRAM_ID;
+
+use crate::error::ErrorCode;
+
+/// Whether the account is delegated, as seen from the base layer: the delegation program owns
+/// it until it is undelegated. Inside the rollup a delegated account keeps this program as
+/// owner, so this cannot tell the two environments apart on its own.
+pub fn is_delegated(account_info: &AccountInfo) -> bool {
+    *account_info.owner == DELEGATION_PROGRAM_ID
+}
+
+/// Guard for base-layer instructions that need the account back from the rollup.
+pub fn require_undelegated(account_info: &AccountInfo) -> Result<()> {
+    require!(!is_delegated(account_info), ErrorCode::AccountDelegated);
+    Ok(())
+}
+
+/// Guard for rollup instructions: a delegated account seen here means the transaction was
+/// sent to the base layer instead of the rollup. It has the same check as `require_undelegated`
+/// and only differs in the error; an account that was never delegated passes on either layer,
+/// so this cannot prove that the instruction runs inside the rollup.
+pub fn require_in_rollup(account_info: &AccountInfo) -> Result<()> {
+    require!(
+        !is_delegated(account_info),
+        ErrorCode::RequiresEphemeralRollup
+    );
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, TradingPair, TradingSession};
+
+/// Look up a pair's market specification by its index in the session config.
+/// The index is also the pair's slot in `Portfolio::positions`.
+pub fn find_trading_pair(session: &TradingSession, pair_index: u16) -> Result<&TradingPair> {
+    session
+        .trading_pairs
+        .get(pair_index as usize)
+        .ok_or(ErrorCode::UnsupportedTradingPair.into())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::{error::ErrorCode, Portfolio};
+
+/// Load a portfolio passed as a remaining account and check that it is the
+/// `[b"portfolio", session, owner]` PDA of the given session.
+pub fn load_session_portfolio<'info>(
+    account_info: &'info AccountInfo<'info>,
+    session_key: &Pubkey,
+    session_id: u64,
+) -> Result<AccountLoader<'info, Portfolio>> {
+    let loader = AccountLoader::<Portfolio>::try_from(account_info)?;
+    check_session_portfolio(&account_info.key(), &*loader.load()?, session_key, session_id)?;
+    Ok(loader)
+}
+
+/// Check that an already loaded portfolio is the `[b"portfolio", session, owner]` PDA of the
+/// given session.
+pub fn check_session_portfolio(
+    key: &Pubkey,
+    portfolio: &Portfolio,
+    session_key: &Pubkey,
+    session_id: u64,
+) -> Result<()> {
+    let expected = Pubkey::create_program_address(
+        &[
+            b"portfolio",
+            session_key.as_ref(),
+            portfolio.owner.as_ref(),
+            &[portfolio.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| ErrorCode::SessionMismatch)?;
+    require_keys_eq!(expected, *key, ErrorCode::SessionMismatch);
+    require!(portfolio.session_id == session_id, ErrorCode::SessionMismatch);
+    Ok(())
+}
@@ -3,4 +3,52 @@ pub use calculate_portfolio_value::*;
 
 
 pub mod calculate_roi;
-pub use calculate_roi::*;
\ No newline at end of file
+pub use calculate_roi::*;
+
+pub mod rank_leaderboard;
+pub use rank_leaderboard::*;
+
+pub mod load_session_portfolio;
+pub use load_session_portfolio::*;
+
+pub mod zero_copy;
+pub use zero_copy::*;
+
+pub mod record_trade;
+pub use record_trade::*;
+
+pub mod track_equity;
+pub use track_equity::*;
+
+pub mod calculate_trading_costs;
+pub use calculate_trading_costs::*;
+
+pub mod find_trading_pair;
+pub use find_trading_pair::*;
+
+pub mod resolve_order_quantity;
+pub use resolve_order_quantity::*;
+
+pub mod check_price_protection;
+pub use check_price_protection::*;
+
+pub mod check_risk_limits;
+pub use check_risk_limits::*;
+
+pub mod validate_trading_pairs;
+pub use validate_trading_pairs::*;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod twap;
+pub use twap::*;
+
+pub mod check_circuit_breaker;
+pub use check_circuit_breaker::*;
+
+pub mod delegation;
+pub use delegation::*;
+
+pub mod rollup_accounts;
+pub use rollup_accounts::*;
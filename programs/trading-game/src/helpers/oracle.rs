@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::{
+    self as pyth_receiver,
+    price_update::{PriceUpdateV2, VerificationLevel},
+    PYTH_PUSH_ORACLE_ID,
+};
+use switchboard_on_demand::{PullFeedAccountData, ON_DEMAND_DEVNET_PID, ON_DEMAND_MAINNET_PID};
+
+use crate::{
+    calculate_twap, error::ErrorCode, find_price_twap, MockPriceFeed, OracleSource, Price,
+    PriceMode, Rounding, TradingPair,
+};
+
+/// Oracle price normalized to the 6-decimal quote format
+pub struct OraclePrice {
+    pub price: Price,
+    pub publish_time: i64,
+}
+
+/// Check the oracle accounts passed to an instruction before any pair is looked up in them.
+pub fn check_price_accounts(accounts: &[AccountInfo]) -> Result<()> {
+    for (idx, account_info) in accounts.iter().enumerate() {
+        require!(
+            !accounts[..idx].iter().any(|a| a.key == account_info.key),
+            ErrorCode::DuplicatePriceAccount
+        );
+    }
+    Ok(())
+}
+
+/// Pick the account that prices `pair` out of `accounts`; `None` for sources that need no
+/// account. Each pair maps to exactly one account, and a missing one is logged by symbol.
+pub fn find_price_account<'a, 'info>(
+    pair: &TradingPair,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<Option<&'a AccountInfo<'info>>> {
+    let found = match &pair.oracle {
+        OracleSource::Manual { .. } => return Ok(None),
+        OracleSource::PythPull { feed_id } => {
+            // pull updates can be posted to any address, so match on the feed they carry
+            let mut found = None;
+            for account_info in accounts.iter().filter(|a| *a.owner == pyth_receiver::ID) {
+                let price_update =
+                    PriceUpdateV2::try_deserialize(&mut &account_info.data.borrow()[..])?;
+                if price_update.price_message.feed_id == *feed_id {
+                    require!(found.is_none(), ErrorCode::DuplicatePriceAccount);
+                    found = Some(account_info);
+                }
+            }
+            found
+        }
+        OracleSource::PythPush { feed_id, shard_id } => {
+            let feed_address = pyth_push_feed_address(feed_id, *shard_id);
+            accounts.iter().find(|a| *a.key == feed_address)
+        }
+        OracleSource::Switchboard { feed } | OracleSource::Mock { feed } => {
+            accounts.iter().find(|a| a.key == feed)
+        }
+    };
+
+    match found {
+        Some(account_info) => Ok(Some(account_info)),
+        None => {
+            msg!("No price account passed for {}", pair.symbol);
+            Err(ErrorCode::PriceFeedNotFound.into())
+        }
+    }
+}
+
+/// Read a pair's price from its configured source. `account` must be the one
+/// `find_price_account` picks for the pair and may be `None` for manual prices.
+/// Prices older than `maximum_age` seconds are rejected; a manual price is as old as its
+/// `updated_at`, so the authority has to keep setting it for trading to continue.
+pub fn read_oracle_price(
+    source: &OracleSource,
+    account: Option<&AccountInfo>,
+    clock: &Clock,
+    maximum_age: u64,
+) -> Result<OraclePrice> {
+    read_source_price(source, account, clock, maximum_age, false)
+}
+
+/// Like `read_oracle_price`, but reads Pyth's EMA price. Other sources publish no EMA and
+/// return their spot price.
+pub fn read_oracle_ema_price(
+    source: &OracleSource,
+    account: Option<&AccountInfo>,
+    clock: &Clock,
+    maximum_age: u64,
+) -> Result<OraclePrice> {
+    read_source_price(source, account, clock, maximum_age, true)
+}
+
+/// Mark price of the pair at `pair_index` in `mode`, with its oracle or TWAP account looked up
+/// in `accounts`.
+pub fn read_mark_price(
+    pair: &TradingPair,
+    pair_index: u16,
+    mode: PriceMode,
+    session_key: &Pubkey,
+    accounts: &[AccountInfo],
+    clock: &Clock,
+    maximum_age: u64,
+) -> Result<OraclePrice> {
+    match mode {
+        PriceMode::Spot => {
+            let account = find_price_account(pair, accounts)?;
+            read_oracle_price(&pair.oracle, account, clock, maximum_age)
+        }
+        PriceMode::Ema => {
+            let account = find_price_account(pair, accounts)?;
+            read_oracle_ema_price(&pair.oracle, account, clock, maximum_age)
+        }
+        PriceMode::Twap => {
+            let Some(twap) = find_price_twap(session_key, pair_index, accounts)? else {
+                msg!("No TWAP account passed for {}", pair.symbol);
+                return Err(ErrorCode::PriceFeedNotFound.into());
+            };
+            calculate_twap(&twap, clock.unix_timestamp, maximum_age)
+        }
+    }
+}
+
+fn read_source_price(
+    source: &OracleSource,
+    account: Option<&AccountInfo>,
+    clock: &Clock,
+    maximum_age: u64,
+    use_ema: bool,
+) -> Result<OraclePrice> {
+    let oracle_price = match source {
+        OracleSource::Manual { price, updated_at } => {
+            // in the rollup this is the mirror's copy, which lags until the next sync
+            check_price_age(clock, *updated_at, maximum_age)?;
+            Ok(OraclePrice {
+                price: Price::new(*price),
+                publish_time: *updated_at,
+            })
+        }
+        OracleSource::PythPull { feed_id } => {
+            let account_info = account.ok_or(ErrorCode::PriceFeedNotFound)?;
+            read_pyth_price(account_info, feed_id, clock, maximum_age, use_ema)
+        }
+        OracleSource::PythPush { feed_id, shard_id } => {
+            let account_info = account.ok_or(ErrorCode::PriceFeedNotFound)?;
+            require!(
+                *account_info.key == pyth_push_feed_address(feed_id, *shard_id),
+                ErrorCode::OracleAccountMismatch
+            );
+            read_pyth_price(account_info, feed_id, clock, maximum_age, use_ema)
+        }
+        OracleSource::Switchboard { feed } => {
+            let account_info = account.ok_or(ErrorCode::PriceFeedNotFound)?;
+            require!(
+                account_info.key == feed
+                    && (*account_info.owner == ON_DEMAND_MAINNET_PID
+                        || *account_info.owner == ON_DEMAND_DEVNET_PID),
+                ErrorCode::OracleAccountMismatch
+            );
+            let feed_data = PullFeedAccountData::parse(account_info.data.borrow())
+                .map_err(|_| ErrorCode::InvalidPriceData)?;
+
+            let publish_time = feed_data.result_ts();
+            check_price_age(clock, publish_time, maximum_age)?;
+
+            // Switchboard results are fixed-point decimals with up to 18 places
+            let value = feed_data
+                .value(clock.slot)
+                .map_err(|_| ErrorCode::StalePriceData)?;
+            Ok(OraclePrice {
+                price: Price::from_oracle(
+                    value.mantissa(),
+                    -(value.scale() as i32),
+                    Rounding::Down,
+                )?,
+                publish_time,
+            })
+        }
+        OracleSource::Mock { feed } => {
+            require!(cfg!(feature = "mock-oracle"), ErrorCode::MockOracleDisabled);
+            let account_info = account.ok_or(ErrorCode::PriceFeedNotFound)?;
+            require!(
+                account_info.key == feed && *account_info.owner == crate::ID,
+                ErrorCode::OracleAccountMismatch
+            );
+            let mock = MockPriceFeed::try_deserialize(&mut &account_info.data.borrow()[..])?;
+
+            // staleness is checked like a real feed so tests can script stale prices too
+            check_price_age(clock, mock.publish_time, maximum_age)?;
+            Ok(OraclePrice {
+                price: Price::new(mock.price),
+                publish_time: mock.publish_time,
+            })
+        }
+    }?;
+
+    // a zero or negative price, including one that rounds to zero, would give free fills
+    require!(oracle_price.price.raw() > 0, ErrorCode::InvalidPriceData);
+    Ok(oracle_price)
+}
+
+fn check_price_age(clock: &Clock, publish_time: i64, maximum_age: u64) -> Result<()> {
+    let price_age = clock
+        .unix_timestamp
+        .checked_sub(publish_time)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(price_age <= maximum_age as i64, ErrorCode::StalePriceData);
+    Ok(())
+}
+
+fn read_pyth_price(
+    account_info: &AccountInfo,
+    feed_id: &[u8; 32],
+    clock: &Clock,
+    maximum_age: u64,
+    use_ema: bool,
+) -> Result<OraclePrice> {
+    // pull updates and sponsored push feeds are both written by the receiver program
+    require!(
+        *account_info.owner == pyth_receiver::ID,
+        ErrorCode::OracleAccountMismatch
+    );
+    let price_update = PriceUpdateV2::try_deserialize(&mut &account_info.data.borrow()[..])?;
+    require!(
+        price_update.price_message.feed_id == *feed_id,
+        ErrorCode::OracleAccountMismatch
+    );
+    require!(
+        price_update.verification_level == VerificationLevel::Full,
+        ErrorCode::InsufficientVerification
+    );
+
+    let price_data = price_update
+        .get_price_no_older_than(clock, maximum_age, feed_id)
+        .map_err(|_| ErrorCode::StalePriceData)?;
+    // the EMA shares the spot price's exponent and publish time
+    let price = if use_ema {
+        price_update.price_message.ema_price
+    } else {
+        price_data.price
+    };
+    Ok(OraclePrice {
+        price: Price::from_oracle(price, price_data.exponent, Rounding::Down)?,
+        publish_time: price_data.publish_time,
+    })
+}
+
+fn pyth_push_feed_address(feed_id: &[u8; 32], shard_id: u16) -> Pubkey {
+    Pubkey::find_program_address(&[&shard_id.to_le_bytes(), feed_id], &PYTH_PUSH_ORACLE_ID).0
+}
@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+use crate::{
+    calculate_roi, error::ErrorCode, Leaderboard, LeaderboardEntry, Portfolio, RanksChanged,
+    MAX_LEADERBOARD_ENTRIES,
+};
+
+/// Insert or refresh the leaderboard entry for a portfolio owner. Does not re-rank.
+pub fn upsert_leaderboard_entry(
+    leaderboard: &mut Leaderboard,
+    portfolio: &Portfolio,
+    timestamp: i64,
+) -> Result<()> {
+    let num_entries = leaderboard.num_entries as usize;
+    let idx = match leaderboard.entries[..num_entries]
+        .iter()
+        .position(|e| e.user == portfolio.owner)
+    {
+        Some(idx) => idx,
+        None => {
+            require!(
+                num_entries < MAX_LEADERBOARD_ENTRIES,
+                ErrorCode::LeaderboardFull
+            );
+            leaderboard.num_entries += 1;
+            num_entries
+        }
+    };
+
+    leaderboard.entries[idx] = LeaderboardEntry {
+        user: portfolio.owner,
+        total_pnl: portfolio.realized_pnl + portfolio.unrealized_pnl,
+        roi_percentage: calculate_roi(portfolio)?,
+        last_updated: timestamp,
+        num_trades: portfolio.num_trades,
+        rank: leaderboard.entries[idx].rank,
+        max_drawdown_bps: portfolio.max_drawdown_bps,
+        _padding: [0; 4],
+    };
+
+    Ok(())
+}
+
+/// Sort entries by total PnL (descending) and assign 1-based ranks, emitting one `RanksChanged`
+/// when any rank moved. Call after `Leaderboard::touch` so the event carries the new version.
+pub fn rank_leaderboard(leaderboard: &mut Leaderboard) {
+    if let Some(ranks_changed) = assign_ranks(leaderboard) {
+        emit!(ranks_changed);
+    }
+}
+
+/// Sort and rank the entries; returns the change covering the span of ranks that moved.
+fn assign_ranks(leaderboard: &mut Leaderboard) -> Option<RanksChanged> {
+    let (session_id, version) = (leaderboard.session_id, leaderboard.version);
+    let num_entries = leaderboard.num_entries as usize;
+    let entries = &mut leaderboard.entries[..num_entries];
+    entries.sort_by_key(|e| std::cmp::Reverse(e.total_pnl));
+
+    let moved = |idx: &usize| entries[*idx].rank != (*idx + 1) as u32;
+    let first = (0..num_entries).find(moved);
+    let last = (0..num_entries).rfind(moved);
+    let ranks_changed = first.zip(last).map(|(first, last)| {
+        let span = &entries[first..=last];
+        RanksChanged {
+            session_id,
+            first_rank: (first + 1) as u32,
+            // ranks are bounded by MAX_LEADERBOARD_ENTRIES
+            previous_ranks: span.iter().map(|e| e.rank as u16).collect(),
+            new_users: span.iter().filter(|e| e.rank == 0).map(|e| e.user).collect(),
+            version,
+        }
+    });
+
+    for (idx, entry) in entries.iter_mut().enumerate() {
+        entry.rank = (idx + 1) as u32;
+    }
+    ranks_changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn user(id: u8) -> Pubkey {
+        Pubkey::new_from_array([id; 32])
+    }
+
+    fn upsert(leaderboard: &mut Leaderboard, id: u8, total_pnl: i64) {
+        let mut portfolio = Portfolio::zeroed();
+        portfolio.owner = user(id);
+        portfolio.realized_pnl = total_pnl;
+        upsert_leaderboard_entry(leaderboard, &portfolio, 0).unwrap();
+    }
+
+    fn ranked_users(leaderboard: &Leaderboard) -> Vec<(Pubkey, u32)> {
+        leaderboard.entries[..leaderboard.num_entries as usize]
+            .iter()
+            .map(|e| (e.user, e.rank))
+            .collect()
+    }
+
+    /// Leaderboard of users 1..=n ranked in that order
+    fn ranked(n: u8) -> Leaderboard {
+        let mut leaderboard = Leaderboard::zeroed();
+        for id in 1..=n {
+            upsert(&mut leaderboard, id, 1_000 - id as i64);
+        }
+        assign_ranks(&mut leaderboard);
+        leaderboard
+    }
+
+    #[test]
+    fn new_users_are_reported_with_previous_rank_zero() {
+        let mut leaderboard = Leaderboard::zeroed();
+        upsert(&mut leaderboard, 1, 10);
+        upsert(&mut leaderboard, 2, 20);
+
+        let change = assign_ranks(&mut leaderboard).unwrap();
+        assert_eq!(change.first_rank, 1);
+        assert_eq!(change.previous_ranks, vec![0, 0]);
+        assert_eq!(change.new_users, vec![user(2), user(1)]);
+        assert_eq!(ranked_users(&leaderboard), vec![(user(2), 1), (user(1), 2)]);
+    }
+
+    #[test]
+    fn unchanged_order_reports_nothing() {
+        let mut leaderboard = ranked(3);
+        upsert(&mut leaderboard, 2, 998);
+        assert!(assign_ranks(&mut leaderboard).is_none());
+    }
+
+    #[test]
+    fn span_covers_only_the_ranks_that_moved() {
+        let mut leaderboard = ranked(5);
+        // user 4 overtakes users 2 and 3; ranks 1 and 5 stay put
+        upsert(&mut leaderboard, 4, 999);
+
+        let change = assign_ranks(&mut leaderboard).unwrap();
+        assert_eq!(change.first_rank, 2);
+        assert_eq!(change.previous_ranks, vec![4, 2, 3]);
+        assert!(change.new_users.is_empty());
+        assert_eq!(
+            ranked_users(&leaderboard),
+            vec![
+                (user(1), 1),
+                (user(4), 2),
+                (user(2), 3),
+                (user(3), 4),
+                (user(5), 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn new_user_inside_the_span_shifts_everyone_below() {
+        let mut leaderboard = ranked(3);
+        upsert(&mut leaderboard, 9, 998);
+
+        let change = assign_ranks(&mut leaderboard).unwrap();
+        // the newcomer ties with user 2 and the stable sort keeps the existing entry ahead
+        assert_eq!(change.first_rank, 3);
+        assert_eq!(change.previous_ranks, vec![0, 3]);
+        assert_eq!(change.new_users, vec![user(9)]);
+        assert_eq!(leaderboard.num_entries, 4);
+    }
+
+    #[test]
+    fn upsert_refreshes_an_existing_entry_and_keeps_its_rank() {
+        let mut leaderboard = ranked(2);
+        upsert(&mut leaderboard, 2, 5_000);
+        assert_eq!(leaderboard.num_entries, 2);
+        assert_eq!(leaderboard.entries[1].total_pnl, 5_000);
+        assert_eq!(leaderboard.entries[1].rank, 2);
+    }
+
+    #[test]
+    fn upsert_rejects_a_new_user_once_full() {
+        let mut leaderboard = Leaderboard::zeroed();
+        leaderboard.num_entries = MAX_LEADERBOARD_ENTRIES as u32;
+        let mut portfolio = Portfolio::zeroed();
+        portfolio.owner = user(1);
+        assert_eq!(
+            upsert_leaderboard_entry(&mut leaderboard, &portfolio, 0).unwrap_err(),
+            ErrorCode::LeaderboardFull.into()
+        );
+    }
+}
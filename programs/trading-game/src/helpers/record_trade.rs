@@ -0,0 +1,80 @@
+use crate::{TradeHistory, TradeRecord, TRADE_HISTORY_CAPACITY};
+
+/// Append a fill to the ring buffer, overwriting the oldest record once full.
+pub fn record_trade(history: &mut TradeHistory, record: TradeRecord) {
+    if history.records.len() < TRADE_HISTORY_CAPACITY {
+        history.records.push(record);
+    } else {
+        history.records[history.head as usize] = record;
+        history.head = ((history.head as usize + 1) % TRADE_HISTORY_CAPACITY) as u16;
+    }
+    history.total_recorded = history.total_recorded.saturating_add(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderSide;
+
+    fn history() -> TradeHistory {
+        TradeHistory {
+            portfolio: Default::default(),
+            head: 0,
+            total_recorded: 0,
+            records: Vec::new(),
+            bump: 0,
+        }
+    }
+
+    fn fill(timestamp: i64) -> TradeRecord {
+        TradeRecord {
+            pair_index: 0,
+            side: OrderSide::Buy,
+            quantity: 1,
+            price: 1,
+            timestamp,
+            realized_pnl: 0,
+            fee: 0,
+        }
+    }
+
+    fn timestamps(history: &TradeHistory) -> Vec<i64> {
+        history.records.iter().map(|r| r.timestamp).collect()
+    }
+
+    #[test]
+    fn fills_are_appended_until_the_buffer_is_full() {
+        let mut history = history();
+        for timestamp in 0..TRADE_HISTORY_CAPACITY as i64 {
+            record_trade(&mut history, fill(timestamp));
+        }
+        assert_eq!(history.records.len(), TRADE_HISTORY_CAPACITY);
+        assert_eq!(history.head, 0);
+        assert_eq!(history.total_recorded, TRADE_HISTORY_CAPACITY as u64);
+    }
+
+    #[test]
+    fn full_buffer_overwrites_the_oldest_fill() {
+        let mut history = history();
+        let total = TRADE_HISTORY_CAPACITY as i64 + 2;
+        for timestamp in 0..total {
+            record_trade(&mut history, fill(timestamp));
+        }
+        assert_eq!(history.records.len(), TRADE_HISTORY_CAPACITY);
+        assert_eq!(history.head, 2);
+        assert_eq!(history.total_recorded, total as u64);
+        let records = timestamps(&history);
+        assert_eq!(records[..2], [total - 2, total - 1]);
+        assert_eq!(records[2], 2);
+    }
+
+    #[test]
+    fn head_wraps_back_to_the_first_slot() {
+        let mut history = history();
+        for timestamp in 0..2 * TRADE_HISTORY_CAPACITY as i64 {
+            record_trade(&mut history, fill(timestamp));
+        }
+        assert_eq!(history.head, 0);
+        assert_eq!(timestamps(&history)[0], TRADE_HISTORY_CAPACITY as i64);
+    }
+}
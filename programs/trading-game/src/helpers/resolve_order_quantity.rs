@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::{
+    calculate_fill_price, calculate_slippage_bps, error::ErrorCode, Money, OrderAmount, OrderSide,
+    Portfolio, Price, Quantity, Rounding, TradingSession, BPS_DENOMINATOR,
+};
+
+/// Turn an `OrderAmount` into a base quantity at the current oracle price.
+///
+/// Notional amounts include slippage and taker fees, so a buy never spends more than asked;
+/// the result is rounded down to the pair's lot size.
+pub fn resolve_order_quantity(
+    amount: &OrderAmount,
+    side: &OrderSide,
+    portfolio: &Portfolio,
+    session: &TradingSession,
+    pair_index: usize,
+    oracle_price: Price,
+) -> Result<Quantity> {
+    let notional = match (amount, side) {
+        (OrderAmount::Quantity(quantity), _) => return Ok(Quantity::new(*quantity)),
+        (OrderAmount::ClosePosition, OrderSide::Sell) => {
+            let quantity = portfolio.positions[pair_index].quantity;
+            require!(quantity > 0, ErrorCode::NoPosition);
+            return Ok(Quantity::new(quantity));
+        }
+        (OrderAmount::Notional(notional), _) => Money::from_unsigned(*notional)?,
+        (OrderAmount::PercentOfCash(bps), OrderSide::Buy) => {
+            require!(
+                (*bps as i128) <= BPS_DENOMINATOR,
+                ErrorCode::InvalidOrderAmount
+            );
+            Money::new(portfolio.cash_balance).mul_bps(*bps, Rounding::Down)?
+        }
+        _ => return Err(ErrorCode::InvalidOrderAmount.into()),
+    };
+
+    // Slippage at the requested notional is an upper bound for the (smaller) resolved order
+    let pair = &session.trading_pairs[pair_index];
+    let slippage_bps = calculate_slippage_bps(&session.fees, notional);
+    let fill_price = calculate_fill_price(oracle_price, side, slippage_bps)?;
+    // A buy spends `notional` including its fee; a sell is sized on gross proceeds
+    let budget = match side {
+        OrderSide::Buy => {
+            notional.checked_sub(notional.mul_bps(session.fees.taker_fee_bps, Rounding::Up)?)?
+        }
+        OrderSide::Sell => notional,
+    };
+    let quantity = Quantity::from_notional(budget, fill_price, pair.base_unit(), Rounding::Down)?;
+
+    Ok(Quantity::new(
+        quantity.raw() - quantity.raw() % pair.lot_size,
+    ))
+}
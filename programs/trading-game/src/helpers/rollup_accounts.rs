@@ -0,0 +1,132 @@
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::prelude::*;
+use std::cell::{Ref, RefMut};
+
+use crate::{
+    check_session_portfolio, error::ErrorCode, load_zero_copy, load_zero_copy_mut,
+    require_in_rollup, Leaderboard, Portfolio, PriceGuard, SessionMirror, TradeHistory,
+};
+
+// Rollup instructions take every account that may be delegated as an unchecked account and
+// load it with these helpers. Anchor's owner check on a typed account would reject a delegated
+// account before the handler runs, so `RequiresEphemeralRollup` could never be reported.
+
+/// Load the session mirror and check that it is the `[b"session_mirror", session]` PDA.
+pub fn load_rollup_session_mirror(account_info: &AccountInfo) -> Result<SessionMirror> {
+    require_in_rollup(account_info)?;
+    check_program_owned(account_info)?;
+    let mirror = SessionMirror::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    check_pda(
+        account_info,
+        &[b"session_mirror", mirror.session.as_ref(), &[mirror.bump]],
+    )?;
+    Ok(mirror)
+}
+
+/// Read a portfolio of the mirrored session.
+pub fn load_rollup_portfolio<'a>(
+    account_info: &'a AccountInfo,
+    mirror: &SessionMirror,
+) -> Result<Ref<'a, Portfolio>> {
+    require_in_rollup(account_info)?;
+    let portfolio = load_zero_copy::<Portfolio>(account_info)?;
+    check_session_portfolio(
+        account_info.key,
+        &portfolio,
+        &mirror.session,
+        mirror.config.session_id,
+    )?;
+    Ok(portfolio)
+}
+
+/// Mutable counterpart of `load_rollup_portfolio`.
+pub fn load_rollup_portfolio_mut<'a>(
+    account_info: &'a AccountInfo,
+    mirror: &SessionMirror,
+) -> Result<RefMut<'a, Portfolio>> {
+    require_in_rollup(account_info)?;
+    let portfolio = load_zero_copy_mut::<Portfolio>(account_info)?;
+    check_session_portfolio(
+        account_info.key,
+        &portfolio,
+        &mirror.session,
+        mirror.config.session_id,
+    )?;
+    Ok(portfolio)
+}
+
+/// Load the mirrored session's `[b"leaderboard", session]` account for writing.
+pub fn load_rollup_leaderboard_mut<'a>(
+    account_info: &'a AccountInfo,
+    mirror: &SessionMirror,
+) -> Result<RefMut<'a, Leaderboard>> {
+    require_in_rollup(account_info)?;
+    let leaderboard = load_zero_copy_mut::<Leaderboard>(account_info)?;
+    check_pda(
+        account_info,
+        &[b"leaderboard", mirror.session.as_ref(), &[leaderboard.bump]],
+    )?;
+    Ok(leaderboard)
+}
+
+/// Load the `[b"price_guard", session, pair_index]` account of a mirrored pair for writing.
+pub fn load_rollup_price_guard_mut<'a>(
+    account_info: &'a AccountInfo,
+    mirror: &SessionMirror,
+    pair_index: u16,
+) -> Result<RefMut<'a, PriceGuard>> {
+    require_in_rollup(account_info)?;
+    let guard = load_zero_copy_mut::<PriceGuard>(account_info)?;
+    check_pda(
+        account_info,
+        &[
+            b"price_guard",
+            mirror.session.as_ref(),
+            &pair_index.to_le_bytes(),
+            &[guard.bump],
+        ],
+    )?;
+    Ok(guard)
+}
+
+/// Load the trade history of `portfolio_key`.
+pub fn load_rollup_trade_history(
+    account_info: &AccountInfo,
+    portfolio_key: &Pubkey,
+) -> Result<TradeHistory> {
+    require_in_rollup(account_info)?;
+    // `try_deserialize` only checks the discriminator; only this program creates trade
+    // histories, always at the portfolio's PDA
+    check_program_owned(account_info)?;
+    let trade_history = TradeHistory::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        trade_history.portfolio,
+        *portfolio_key,
+        ErrorCode::SessionMismatch
+    );
+    Ok(trade_history)
+}
+
+/// Write back a trade history loaded with `load_rollup_trade_history`.
+pub fn store_trade_history(account_info: &AccountInfo, trade_history: &TradeHistory) -> Result<()> {
+    require!(account_info.is_writable, AnchorErrorCode::AccountNotMutable);
+    let mut data = account_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    trade_history.try_serialize(&mut writer)
+}
+
+fn check_program_owned(account_info: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *account_info.owner,
+        crate::ID,
+        AnchorErrorCode::AccountOwnedByWrongProgram
+    );
+    Ok(())
+}
+
+fn check_pda(account_info: &AccountInfo, seeds: &[&[u8]]) -> Result<()> {
+    let expected = Pubkey::create_program_address(seeds, &crate::ID)
+        .map_err(|_| AnchorErrorCode::ConstraintSeeds)?;
+    require_keys_eq!(expected, *account_info.key, AnchorErrorCode::ConstraintSeeds);
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use crate::{
+    error::ErrorCode, EquitySample, Money, Portfolio, Rounding, EQUITY_SAMPLE_CAPACITY,
+    EQUITY_SAMPLE_INTERVAL,
+};
+
+/// Update high-water mark and max drawdown from the current `total_value`, and append an
+/// equity sample if at least `EQUITY_SAMPLE_INTERVAL` seconds passed since the last one.
+pub fn track_equity(portfolio: &mut Portfolio, timestamp: i64) -> Result<()> {
+    let total_value = portfolio.total_value;
+
+    if total_value > portfolio.high_water_mark {
+        portfolio.high_water_mark = total_value;
+    } else if portfolio.high_water_mark > 0 {
+        let high_water_mark = Money::new(portfolio.high_water_mark);
+        let drawdown_bps = high_water_mark
+            .checked_sub(Money::new(total_value))?
+            .ratio_bps(high_water_mark, Rounding::Up)?;
+        let drawdown_bps = u32::try_from(drawdown_bps).map_err(|_| ErrorCode::MathOverflow)?;
+        portfolio.max_drawdown_bps = portfolio.max_drawdown_bps.max(drawdown_bps);
+    }
+
+    let count = portfolio.equity_count as usize;
+    let head = portfolio.equity_head as usize;
+    if count > 0 {
+        let newest_idx = if count < EQUITY_SAMPLE_CAPACITY {
+            count - 1
+        } else {
+            (head + EQUITY_SAMPLE_CAPACITY - 1) % EQUITY_SAMPLE_CAPACITY
+        };
+        if timestamp - portfolio.equity_samples[newest_idx].timestamp < EQUITY_SAMPLE_INTERVAL {
+            return Ok(());
+        }
+    }
+
+    let sample = EquitySample {
+        timestamp,
+        total_value,
+    };
+    if count < EQUITY_SAMPLE_CAPACITY {
+        portfolio.equity_samples[count] = sample;
+        portfolio.equity_count += 1;
+    } else {
+        portfolio.equity_samples[head] = sample;
+        portfolio.equity_head = ((head + 1) % EQUITY_SAMPLE_CAPACITY) as u16;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn portfolio_at(high_water_mark: i64, total_value: i64) -> Portfolio {
+        let mut portfolio = Portfolio::zeroed();
+        portfolio.high_water_mark = high_water_mark;
+        portfolio.total_value = total_value;
+        portfolio
+    }
+
+    fn mark(portfolio: &mut Portfolio, total_value: i64, timestamp: i64) {
+        portfolio.total_value = total_value;
+        track_equity(portfolio, timestamp).unwrap();
+    }
+
+    #[test]
+    fn drawdown_is_measured_from_the_high_water_mark_rounding_up() {
+        let mut portfolio = portfolio_at(0, 3_000);
+        track_equity(&mut portfolio, 0).unwrap();
+        assert_eq!(portfolio.high_water_mark, 3_000);
+        mark(&mut portfolio, 2_000, 60);
+        assert_eq!(portfolio.high_water_mark, 3_000);
+        // a third of the peak is 3_333.3 bps
+        assert_eq!(portfolio.max_drawdown_bps, 3_334);
+    }
+
+    #[test]
+    fn max_drawdown_keeps_the_largest_decline() {
+        let mut portfolio = portfolio_at(0, 1_000);
+        track_equity(&mut portfolio, 0).unwrap();
+        mark(&mut portfolio, 500, 60);
+        mark(&mut portfolio, 2_000, 120);
+        mark(&mut portfolio, 1_800, 180);
+        assert_eq!(portfolio.high_water_mark, 2_000);
+        assert_eq!(portfolio.max_drawdown_bps, 5_000);
+    }
+
+    #[test]
+    fn value_below_zero_is_a_drawdown_beyond_the_peak() {
+        let mut portfolio = portfolio_at(100, -50);
+        track_equity(&mut portfolio, 0).unwrap();
+        assert_eq!(portfolio.max_drawdown_bps, 15_000);
+    }
+
+    #[test]
+    fn zero_high_water_mark_records_no_drawdown() {
+        let mut portfolio = portfolio_at(0, 0);
+        track_equity(&mut portfolio, 0).unwrap();
+        mark(&mut portfolio, -500, 60);
+        assert_eq!(portfolio.high_water_mark, 0);
+        assert_eq!(portfolio.max_drawdown_bps, 0);
+    }
+
+    #[test]
+    fn negative_high_water_mark_only_rises() {
+        let mut portfolio = portfolio_at(-100, -200);
+        track_equity(&mut portfolio, 0).unwrap();
+        assert_eq!(portfolio.high_water_mark, -100);
+        assert_eq!(portfolio.max_drawdown_bps, 0);
+        mark(&mut portfolio, -50, 60);
+        assert_eq!(portfolio.high_water_mark, -50);
+        assert_eq!(portfolio.max_drawdown_bps, 0);
+    }
+
+    #[test]
+    fn samples_closer_than_the_interval_are_skipped() {
+        let mut portfolio = portfolio_at(0, 1_000);
+        track_equity(&mut portfolio, 1_000).unwrap();
+        mark(&mut portfolio, 900, 1_000 + EQUITY_SAMPLE_INTERVAL - 1);
+        assert_eq!(portfolio.equity_count, 1);
+        // the drawdown is still tracked on skipped samples
+        assert_eq!(portfolio.max_drawdown_bps, 1_000);
+        mark(&mut portfolio, 900, 1_000 + EQUITY_SAMPLE_INTERVAL);
+        assert_eq!(portfolio.equity_count, 2);
+        assert_eq!(portfolio.equity_samples[1].total_value, 900);
+    }
+
+    #[test]
+    fn full_buffer_overwrites_the_oldest_sample() {
+        let mut portfolio = portfolio_at(0, 0);
+        let extra = 2;
+        for idx in 0..EQUITY_SAMPLE_CAPACITY + extra {
+            mark(&mut portfolio, idx as i64, idx as i64 * EQUITY_SAMPLE_INTERVAL);
+        }
+        assert_eq!(portfolio.equity_count as usize, EQUITY_SAMPLE_CAPACITY);
+        assert_eq!(portfolio.equity_head as usize, extra);
+        assert_eq!(
+            portfolio.equity_samples[extra - 1].total_value,
+            (EQUITY_SAMPLE_CAPACITY + extra - 1) as i64
+        );
+        assert_eq!(portfolio.equity_samples[extra].total_value, extra as i64);
+
+        // after the wrap the interval is measured from the newest sample, not the last slot
+        let newest = (EQUITY_SAMPLE_CAPACITY + extra - 1) as i64 * EQUITY_SAMPLE_INTERVAL;
+        mark(&mut portfolio, -1, newest + EQUITY_SAMPLE_INTERVAL - 1);
+        assert_eq!(portfolio.equity_head as usize, extra);
+    }
+
+    #[test]
+    fn head_wraps_back_to_the_first_slot() {
+        let mut portfolio = portfolio_at(0, 0);
+        for idx in 0..EQUITY_SAMPLE_CAPACITY * 2 {
+            mark(&mut portfolio, 1, idx as i64 * EQUITY_SAMPLE_INTERVAL);
+        }
+        assert_eq!(portfolio.equity_head, 0);
+        assert_eq!(
+            portfolio.equity_samples[EQUITY_SAMPLE_CAPACITY - 1].timestamp,
+            (EQUITY_SAMPLE_CAPACITY * 2 - 1) as i64 * EQUITY_SAMPLE_INTERVAL
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, OraclePrice, Price, PriceSample, PriceTwap, Rounding, MARK_PRICE_MAX_AGE,
+    TWAP_SAMPLE_CAPACITY, TWAP_SAMPLE_INTERVAL, TWAP_WINDOW,
+};
+
+/// Samples from oldest to newest.
+fn chronological_samples(twap: &PriceTwap) -> impl Iterator<Item = &PriceSample> {
+    let (newer, older) = twap.samples.split_at(twap.head as usize);
+    older.iter().chain(newer.iter())
+}
+
+/// Newest sample, if any.
+pub fn latest_price_sample(twap: &PriceTwap) -> Option<&PriceSample> {
+    chronological_samples(twap).last()
+}
+
+/// Append an oracle price unless it was published less than `TWAP_SAMPLE_INTERVAL` seconds
+/// after the latest sample, so cranking fast cannot flush the window. Returns whether the sample
+/// was recorded.
+pub fn push_price_sample(twap: &mut PriceTwap, sample: PriceSample) -> bool {
+    if latest_price_sample(twap).is_some_and(|latest| {
+        sample.publish_time < latest.publish_time.saturating_add(TWAP_SAMPLE_INTERVAL)
+    }) {
+        return false;
+    }
+
+    if twap.samples.len() < TWAP_SAMPLE_CAPACITY {
+        twap.samples.push(sample);
+    } else {
+        twap.samples[twap.head as usize] = sample;
+        twap.head = ((twap.head as usize + 1) % TWAP_SAMPLE_CAPACITY) as u16;
+    }
+    true
+}
+
+/// Time-weighted average over the `TWAP_WINDOW` seconds up to `at`; each sample counts until
+/// the next one was published, the newest until `at`. Samples published after `at` are ignored.
+pub fn calculate_twap(twap: &PriceTwap, at: i64, maximum_age: u64) -> Result<OraclePrice> {
+    let samples: Vec<&PriceSample> = chronological_samples(twap)
+        .filter(|sample| sample.publish_time <= at)
+        .collect();
+    let latest = samples.last().ok_or(ErrorCode::PriceFeedNotFound)?;
+    let price_age = at
+        .checked_sub(latest.publish_time)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(price_age <= maximum_age as i64, ErrorCode::StalePriceData);
+
+    let window_start = at.saturating_sub(TWAP_WINDOW);
+    let mut points: Vec<(Price, i64)> = Vec::with_capacity(samples.len());
+    for (idx, sample) in samples.iter().enumerate() {
+        let start = sample.publish_time.max(window_start);
+        let end = samples.get(idx + 1).map_or(at, |next| next.publish_time);
+        if end > start {
+            points.push((Price::new(sample.price), end - start));
+        }
+    }
+
+    // a sample published this very second has no duration yet
+    let price = if points.is_empty() {
+        Price::new(latest.price)
+    } else {
+        Price::time_weighted_average(&points, Rounding::Down)?
+    };
+    Ok(OraclePrice {
+        price,
+        publish_time: latest.publish_time,
+    })
+}
+
+/// Settlement TWAP over the `TWAP_WINDOW` seconds before `end_time`. Only available once a
+/// sample published at or after `end_time` exists: the samples it covers can then no longer
+/// change, so the result does not depend on when it is read.
+pub fn calculate_settlement_twap(twap: &PriceTwap, end_time: i64) -> Result<OraclePrice> {
+    require!(
+        latest_price_sample(twap).is_some_and(|latest| latest.publish_time >= end_time),
+        ErrorCode::SettlementPriceTooEarly
+    );
+    calculate_twap(twap, end_time, MARK_PRICE_MAX_AGE)
+}
+
+/// Find the `PriceTwap` account of a session pair. Only this program can create accounts
+/// with the `PriceTwap` discriminator, and it only does so at the pair's PDA.
+pub fn find_price_twap(
+    session_key: &Pubkey,
+    pair_index: u16,
+    accounts: &[AccountInfo],
+) -> Result<Option<PriceTwap>> {
+    for account_info in accounts.iter().filter(|a| *a.owner == crate::ID) {
+        let data = account_info.data.borrow();
+        if !data.starts_with(PriceTwap::DISCRIMINATOR) {
+            continue;
+        }
+        let twap = PriceTwap::try_deserialize(&mut &data[..])?;
+        if twap.session == *session_key && twap.pair_index == pair_index {
+            return Ok(Some(twap));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USD: i64 = 1_000_000;
+
+    fn twap_with(samples: &[(i64, i64)]) -> PriceTwap {
+        let mut twap = PriceTwap {
+            session: Pubkey::default(),
+            pair_index: 0,
+            head: 0,
+            samples: Vec::new(),
+            bump: 0,
+        };
+        for &(price, publish_time) in samples {
+            assert!(push_price_sample(&mut twap, PriceSample { price, publish_time }));
+        }
+        twap
+    }
+
+    /// TWAP at `at`, accepting a latest sample as old as the window
+    fn twap_price(twap: &PriceTwap, at: i64) -> i64 {
+        calculate_twap(twap, at, TWAP_WINDOW as u64).unwrap().price.raw()
+    }
+
+    fn twap_error(result: Result<OraclePrice>) -> anchor_lang::error::Error {
+        result.err().expect("expected an error")
+    }
+
+    #[test]
+    fn sample_published_at_the_reference_time_is_the_price() {
+        let twap = twap_with(&[(100 * USD, 1_000)]);
+        let price = calculate_twap(&twap, 1_000, MARK_PRICE_MAX_AGE).unwrap();
+        assert_eq!(price.price.raw(), 100 * USD);
+        assert_eq!(price.publish_time, 1_000);
+    }
+
+    #[test]
+    fn samples_are_weighted_by_how_long_they_were_current() {
+        let twap = twap_with(&[(100 * USD, 1_000), (200 * USD, 1_030)]);
+        // 30s at 100 and 10s at 200
+        assert_eq!(twap_price(&twap, 1_040), 125 * USD);
+    }
+
+    #[test]
+    fn window_clips_samples_at_its_start() {
+        // the first sample ends before the window, the second straddles its start
+        let twap = twap_with(&[
+            (50 * USD, 1_000),
+            (100 * USD, 1_050),
+            (200 * USD, 1_200),
+        ]);
+        let at = 1_100 + TWAP_WINDOW;
+        // window [1_100, 1_400]: 100s at 100, 200s at 200, rounded down
+        assert_eq!(twap_price(&twap, at), 166_666_666);
+    }
+
+    #[test]
+    fn window_of_exactly_twap_window_seconds_counts_the_whole_first_sample() {
+        let twap = twap_with(&[(100 * USD, 1_000), (200 * USD, 1_000 + TWAP_WINDOW / 2)]);
+        assert_eq!(twap_price(&twap, 1_000 + TWAP_WINDOW), 150 * USD);
+    }
+
+    #[test]
+    fn samples_published_after_the_reference_time_are_ignored() {
+        let twap = twap_with(&[(100 * USD, 1_000), (400 * USD, 1_020)]);
+        assert_eq!(twap_price(&twap, 1_010), 100 * USD);
+        assert_eq!(
+            twap_error(calculate_twap(&twap, 999, MARK_PRICE_MAX_AGE)),
+            ErrorCode::PriceFeedNotFound.into()
+        );
+    }
+
+    #[test]
+    fn latest_sample_must_be_within_maximum_age() {
+        let twap = twap_with(&[(100 * USD, 1_000)]);
+        let oldest_accepted = 1_000 + MARK_PRICE_MAX_AGE as i64;
+        assert!(calculate_twap(&twap, oldest_accepted, MARK_PRICE_MAX_AGE).is_ok());
+        assert_eq!(
+            twap_error(calculate_twap(&twap, oldest_accepted + 1, MARK_PRICE_MAX_AGE)),
+            ErrorCode::StalePriceData.into()
+        );
+    }
+
+    #[test]
+    fn samples_closer_than_the_interval_are_skipped() {
+        let mut twap = twap_with(&[(100 * USD, 1_000)]);
+        let early = PriceSample {
+            price: 200 * USD,
+            publish_time: 1_000 + TWAP_SAMPLE_INTERVAL - 1,
+        };
+        assert!(!push_price_sample(&mut twap, early));
+        let on_time = PriceSample {
+            price: 200 * USD,
+            publish_time: 1_000 + TWAP_SAMPLE_INTERVAL,
+        };
+        assert!(push_price_sample(&mut twap, on_time));
+        assert_eq!(twap.samples.len(), 2);
+    }
+
+    #[test]
+    fn full_buffer_overwrites_the_oldest_sample() {
+        let extra = 3;
+        let samples: Vec<(i64, i64)> = (0..TWAP_SAMPLE_CAPACITY + extra)
+            .map(|idx| ((idx as i64 + 1) * USD, idx as i64 * TWAP_SAMPLE_INTERVAL))
+            .collect();
+        let twap = twap_with(&samples);
+
+        assert_eq!(twap.samples.len(), TWAP_SAMPLE_CAPACITY);
+        assert_eq!(twap.head as usize, extra);
+        let latest = latest_price_sample(&twap).unwrap();
+        assert_eq!(latest.price, samples.last().unwrap().0);
+        let chronological: Vec<i64> = chronological_samples(&twap)
+            .map(|sample| sample.publish_time)
+            .collect();
+        let expected: Vec<i64> = samples[extra..].iter().map(|&(_, time)| time).collect();
+        assert_eq!(chronological, expected);
+    }
+
+    #[test]
+    fn head_wraps_back_to_the_first_slot() {
+        let samples: Vec<(i64, i64)> = (0..TWAP_SAMPLE_CAPACITY * 2)
+            .map(|idx| (USD, idx as i64 * TWAP_SAMPLE_INTERVAL))
+            .collect();
+        let twap = twap_with(&samples);
+        assert_eq!(twap.head, 0);
+        assert_eq!(
+            latest_price_sample(&twap).unwrap().publish_time,
+            samples.last().unwrap().1
+        );
+    }
+
+    #[test]
+    fn settlement_twap_waits_for_a_sample_at_or_after_end_time() {
+        let end_time = 2_000;
+        let mut twap = twap_with(&[(100 * USD, end_time - 20)]);
+        assert_eq!(
+            twap_error(calculate_settlement_twap(&twap, end_time)),
+            ErrorCode::SettlementPriceTooEarly.into()
+        );
+
+        push_price_sample(
+            &mut twap,
+            PriceSample {
+                price: 300 * USD,
+                publish_time: end_time,
+            },
+        );
+        // the sample at end_time has no duration inside the window
+        let price = calculate_settlement_twap(&twap, end_time).unwrap();
+        assert_eq!(price.price.raw(), 100 * USD);
+    }
+
+    #[test]
+    fn settlement_twap_does_not_depend_on_later_samples() {
+        let end_time = 2_000;
+        let mut twap = twap_with(&[
+            (100 * USD, end_time - 40),
+            (200 * USD, end_time - 20),
+            (300 * USD, end_time + 5),
+        ]);
+        let settled = calculate_settlement_twap(&twap, end_time).unwrap().price;
+        push_price_sample(
+            &mut twap,
+            PriceSample {
+                price: 900 * USD,
+                publish_time: end_time + 60,
+            },
+        );
+        assert_eq!(calculate_settlement_twap(&twap, end_time).unwrap().price, settled);
+        assert_eq!(settled.raw(), 150 * USD);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{
+    error::ErrorCode, OracleSource, TradingPair, MAX_BASE_DECIMALS, MAX_SYMBOL_LEN,
+    MAX_TRADING_PAIRS,
+};
+use anchor_lang::prelude::*;
+
+/// Check a session's full pair list against the bounds its account layout assumes.
+pub fn validate_trading_pairs(trading_pairs: &[TradingPair]) -> Result<()> {
+    require!(
+        !trading_pairs.is_empty() && trading_pairs.len() <= MAX_TRADING_PAIRS,
+        ErrorCode::TooManyTradingPairs
+    );
+
+    for (idx, pair) in trading_pairs.iter().enumerate() {
+        require!(
+            !pair.symbol.is_empty() && pair.symbol.len() <= MAX_SYMBOL_LEN,
+            ErrorCode::InvalidSymbol
+        );
+        require!(
+            pair.lot_size > 0 && pair.base_decimals <= MAX_BASE_DECIMALS,
+            ErrorCode::InvalidTradingPairConfig
+        );
+        match pair.oracle {
+            OracleSource::Manual { price, .. } => {
+                require!(price > 0, ErrorCode::InvalidPriceData)
+            }
+            OracleSource::Mock { .. } => {
+                require!(cfg!(feature = "mock-oracle"), ErrorCode::MockOracleDisabled)
+            }
+            _ => {}
+        }
+        require!(
+            !trading_pairs[..idx].iter().any(|p| p.symbol == pair.symbol),
+            ErrorCode::DuplicateTradingPair
+        );
+    }
+
+    Ok(())
+}
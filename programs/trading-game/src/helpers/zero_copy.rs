@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::ZeroCopy;
+use std::cell::{Ref, RefMut};
+
+/// Check owner and discriminator the way `AccountLoader` does; returns the data offset.
+fn check_zero_copy<T: ZeroCopy + Owner>(account_info: &AccountInfo) -> Result<usize> {
+    require_keys_eq!(
+        *account_info.owner,
+        T::owner(),
+        AnchorErrorCode::AccountOwnedByWrongProgram
+    );
+    let data = account_info.try_borrow_data()?;
+    let disc_len = T::DISCRIMINATOR.len();
+    require!(
+        data.len() >= disc_len + std::mem::size_of::<T>() && data[..disc_len] == *T::DISCRIMINATOR,
+        AnchorErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(disc_len)
+}
+
+/// Read a zero-copy account in place like `AccountLoader::load`, which needs the account
+/// borrowed for `'info` and so only works on remaining accounts.
+pub fn load_zero_copy<'a, T: ZeroCopy + Owner>(
+    account_info: &'a AccountInfo,
+) -> Result<Ref<'a, T>> {
+    let offset = check_zero_copy::<T>(account_info)?;
+    let data = account_info.try_borrow_data()?;
+    Ok(Ref::map(data, |data| {
+        bytemuck::from_bytes(&data[offset..offset + std::mem::size_of::<T>()])
+    }))
+}
+
+/// Mutable counterpart of `load_zero_copy`, like `AccountLoader::load_mut`.
+pub fn load_zero_copy_mut<'a, T: ZeroCopy + Owner>(
+    account_info: &'a AccountInfo,
+) -> Result<RefMut<'a, T>> {
+    require!(account_info.is_writable, AnchorErrorCode::AccountNotMutable);
+    let offset = check_zero_copy::<T>(account_info)?;
+    let data = account_info.try_borrow_mut_data()?;
+    Ok(RefMut::map(data, |data| {
+        bytemuck::from_bytes_mut(&mut data[offset..offset + std::mem::size_of::<T>()])
+    }))
+}
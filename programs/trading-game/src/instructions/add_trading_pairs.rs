@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, validate_trading_pairs, SettlementPrice, TradingPair, TradingPairsAdded,
+    TradingSession, MAX_TRADING_PAIRS,
+};
+
+#[derive(Accounts)]
+#[instruction(new_pairs: Vec<TradingPair>)]
+pub struct AddTradingPairs<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        // clamped so an oversized list reaches `validate_trading_pairs` instead of failing
+        // the realloc
+        realloc = 8 + TradingSession::space(
+            (session.trading_pairs.len() + new_pairs.len()).min(MAX_TRADING_PAIRS)
+        ),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub session: Account<'info, TradingSession>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// List more pairs on a running session, growing the session account to fit.
+/// Portfolios already have a position slot for every pair up to `MAX_TRADING_PAIRS`; orders
+/// in the rollup see the new pairs once the session mirror is synced.
+pub fn process_add_trading_pairs(
+    ctx: Context<AddTradingPairs>,
+    new_pairs: Vec<TradingPair>,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    require!(session.is_active, ErrorCode::SessionInactive);
+
+    let first_pair_index = session.trading_pairs.len() as u16;
+    session.trading_pairs.extend(new_pairs);
+    let num_pairs = session.trading_pairs.len();
+    session
+        .settlement_prices
+        .resize(num_pairs, SettlementPrice::default());
+    validate_trading_pairs(&session.trading_pairs)?;
+    session.touch_config()?;
+
+    emit!(TradingPairsAdded {
+        session_id: session.session_id,
+        first_pair_index,
+        trading_pairs: session.trading_pairs[first_pair_index as usize..].to_vec(),
+        version: session.config_version,
+    });
+
+    msg!(
+        "Session {} now lists {} trading pairs",
+        session.session_id,
+        session.trading_pairs.len()
+    );
+    Ok(())
+}
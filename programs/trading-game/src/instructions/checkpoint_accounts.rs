@@ -1,17 +1,27 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::ephem::commit_accounts;
-use crate::{state::{Leaderboard, Portfolio, TradingSession}, AccountsCheckpointed};
+use crate::{
+    error::ErrorCode, load_rollup_leaderboard_mut, load_rollup_portfolio_mut,
+    load_rollup_session_mirror, load_rollup_trade_history, AccountsCheckpointed,
+};
 
 #[derive(Accounts)]
 pub struct CheckpointAccounts<'info> {
+    /// CHECK: loaded in the handler after the delegation check
+    pub session_mirror: UncheckedAccount<'info>,
+
+    /// CHECK: loaded in the handler after the delegation check
     #[account(mut)]
-    pub session: Account<'info, TradingSession>,
+    pub portfolio: UncheckedAccount<'info>,
 
+    /// CHECK: loaded in the handler after the delegation check; only passed when the session
+    /// delegates its leaderboard to the rollup
     #[account(mut)]
-    pub portfolio: Account<'info, Portfolio>,
+    pub leaderboard: Option<UncheckedAccount<'info>>,
 
+    /// CHECK: loaded in the handler and matched against the portfolio
     #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
+    pub trade_history: UncheckedAccount<'info>,
 
     /// CHECK: Magic ER context
     #[account(mut)]
@@ -28,21 +38,61 @@ pub struct CheckpointAccounts<'info> {
  /// Commit a set of delegated accounts (checkpoint) while still delegated. This would be invoked by validator or client.
     pub fn process_checkpoint_trading_accounts(ctx: Context<CheckpointAccounts>) -> Result<()> {
         // Commit accounts in-place (the SDK helper expects: magic_context, vec![accounts], magic_program, payer)
+        let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+        let portfolio = ctx.accounts.portfolio.to_account_info();
+        let leaderboard = ctx.accounts.leaderboard.as_ref().map(|l| l.to_account_info());
+        let trade_history = ctx.accounts.trade_history.to_account_info();
+        load_rollup_trade_history(&trade_history, portfolio.key)?;
+        let mut accounts = Vec::with_capacity(3);
+
+        // unchanged accounts are skipped; the committed copy records the version it was taken at
+        let (owner, portfolio_version) = {
+            let mut portfolio_data = load_rollup_portfolio_mut(&portfolio, &mirror)?;
+            let version = portfolio_data
+                .has_uncommitted_changes()
+                .then_some(portfolio_data.version);
+            portfolio_data.committed_version = portfolio_data.version;
+            (portfolio_data.owner, version)
+        };
+        if portfolio_version.is_some() {
+            // fills write the trade history and the portfolio together
+            accounts.push(&portfolio);
+            accounts.push(&trade_history);
+        }
+
+        // the leaderboard only lives in the rollup when the session delegates it
+        let mut leaderboard_version = None;
+        if let Some(leaderboard) = &leaderboard {
+            require!(
+                mirror.config.er_config.delegate_leaderboard,
+                ErrorCode::LeaderboardDelegationDisabled
+            );
+            let mut leaderboard_data = load_rollup_leaderboard_mut(leaderboard, &mirror)?;
+            if leaderboard_data.has_uncommitted_changes() {
+                leaderboard_data.committed_version = leaderboard_data.version;
+                leaderboard_version = Some(leaderboard_data.version);
+                accounts.push(leaderboard);
+            }
+        }
+
+        if accounts.is_empty() {
+            msg!("Nothing to checkpoint for user {}", owner);
+            return Ok(());
+        }
         commit_accounts(
             &ctx.accounts.magic_context,
-            vec![
-                &ctx.accounts.portfolio.to_account_info(),
-                &ctx.accounts.leaderboard.to_account_info(),
-            ],
+            accounts,
             &ctx.accounts.magic_program,
             &ctx.accounts.payer.to_account_info(),
         )?;
 
         emit!(AccountsCheckpointed {
-            session_id: ctx.accounts.session.session_id,
-            user: ctx.accounts.portfolio.owner,
+            session_id: mirror.config.session_id,
+            user: owner,
+            portfolio_version,
+            leaderboard_version,
         });
 
-        msg!("Checkpoint committed for user {}", ctx.accounts.portfolio.owner);
+        msg!("Checkpoint committed for user {}", owner);
         Ok(())
     }
\ No newline at end of file
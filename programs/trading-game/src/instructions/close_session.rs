@@ -1,11 +1,20 @@
 use anchor_lang::prelude::*;
 
-use crate::{error::ErrorCode, SessionClosed, TradingSession};  
+use crate::{
+    error::ErrorCode, load_zero_copy, require_undelegated, Leaderboard, LeaderboardResult,
+    SessionClosed, TradingSession, SETTLEMENT_WINNERS,
+};
   
 #[derive(Accounts)]
 pub struct CloseSession<'info> {
     #[account(mut)]
     pub session: Account<'info, TradingSession>,
+    /// CHECK: loaded in the handler once it is known to be back from the rollup
+    #[account(seeds = [b"leaderboard", session.key().as_ref()], bump)]
+    pub leaderboard: UncheckedAccount<'info>,
+    /// CHECK: only checked to be back from the rollup, so no rollup fill lands after the close
+    #[account(seeds = [b"session_mirror", session.key().as_ref()], bump)]
+    pub session_mirror: UncheckedAccount<'info>,
     pub authority: Signer<'info>,
 }
   
@@ -18,12 +27,32 @@ pub struct CloseSession<'info> {
         let clock = Clock::get()?;
 
         require!(clock.unix_timestamp >= session.end_time, ErrorCode::SessionStillActive);
+        require_undelegated(&ctx.accounts.leaderboard)?;
+        require_undelegated(&ctx.accounts.session_mirror)?;
 
         session.is_active = false;
+        session.touch_config()?;
+
+        let leaderboard = load_zero_copy::<Leaderboard>(&ctx.accounts.leaderboard)?;
+        let leaders: Vec<LeaderboardResult> = leaderboard.entries
+            [..leaderboard.num_entries as usize]
+            .iter()
+            .take(SETTLEMENT_WINNERS as usize)
+            .map(|e| LeaderboardResult {
+                user: e.user,
+                rank: e.rank,
+                total_pnl: e.total_pnl,
+                roi_percentage: e.roi_percentage,
+            })
+            .collect();
 
         emit!(SessionClosed {
             session_id: session.session_id,
             participant_count: session.participant_count,
+            closed_at: clock.unix_timestamp,
+            leaders,
+            leaderboard_version: leaderboard.version,
+            version: session.config_version,
         });
 
         msg!(
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::anchor::delegate;
+
+use crate::{error::ErrorCode, require_undelegated, LeaderboardDelegated, TradingSession};
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateLeaderboard<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+
+    /// CHECK: Will be validated by delegate program
+    #[account(mut, del, seeds = [b"leaderboard", session.key().as_ref()], bump)]
+    pub leaderboard: AccountInfo<'info>,
+}
+
+/// Delegate the session leaderboard to the session's ER validator, when the session is
+/// configured to rank players inside the rollup.
+pub fn process_delegate_leaderboard(ctx: Context<DelegateLeaderboard>) -> Result<()> {
+    require_undelegated(&ctx.accounts.leaderboard)?;
+    let er_config = &ctx.accounts.session.er_config;
+    require!(
+        er_config.delegate_leaderboard,
+        ErrorCode::LeaderboardDelegationDisabled
+    );
+
+    ctx.accounts.delegate_leaderboard(
+        &ctx.accounts.authority,
+        &[b"leaderboard", ctx.accounts.session.key().as_ref()],
+        er_config.delegate_config(),
+    )?;
+
+    emit!(LeaderboardDelegated {
+        session_id: ctx.accounts.session.session_id,
+        validator: er_config.validator,
+    });
+
+    Ok(())
+}
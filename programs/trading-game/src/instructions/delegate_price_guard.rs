@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::anchor::delegate;
+
+use crate::{error::ErrorCode, require_undelegated, PriceGuardDelegated, TradingSession};
+
+#[delegate]
+#[derive(Accounts)]
+#[instruction(pair_index: u16)]
+pub struct DelegatePriceGuard<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+
+    /// CHECK: Will be validated by delegate program
+    #[account(
+        mut,
+        del,
+        seeds = [b"price_guard", session.key().as_ref(), &pair_index.to_le_bytes()],
+        bump
+    )]
+    pub price_guard: AccountInfo<'info>,
+}
+
+/// Delegate a pair's circuit breaker to the session's ER validator, so orders on the pair can
+/// run inside the rollup.
+pub fn process_delegate_price_guard(
+    ctx: Context<DelegatePriceGuard>,
+    pair_index: u16,
+) -> Result<()> {
+    require_undelegated(&ctx.accounts.price_guard)?;
+    let er_config = &ctx.accounts.session.er_config;
+
+    ctx.accounts.delegate_price_guard(
+        &ctx.accounts.authority,
+        &[
+            b"price_guard",
+            ctx.accounts.session.key().as_ref(),
+            &pair_index.to_le_bytes(),
+        ],
+        er_config.delegate_config(),
+    )?;
+
+    emit!(PriceGuardDelegated {
+        session_id: ctx.accounts.session.session_id,
+        pair_index,
+        validator: er_config.validator,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::anchor::delegate;
+
+use crate::{error::ErrorCode, require_undelegated, SessionMirrorDelegated, TradingSession};
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateSessionMirror<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+
+    /// CHECK: Will be validated by delegate program
+    #[account(mut, del, seeds = [b"session_mirror", session.key().as_ref()], bump)]
+    pub session_mirror: AccountInfo<'info>,
+}
+
+/// Delegate the session mirror to the session's ER validator, so orders inside the rollup read
+/// current session config.
+pub fn process_delegate_session_mirror(ctx: Context<DelegateSessionMirror>) -> Result<()> {
+    require_undelegated(&ctx.accounts.session_mirror)?;
+    let er_config = &ctx.accounts.session.er_config;
+
+    ctx.accounts.delegate_session_mirror(
+        &ctx.accounts.authority,
+        &[b"session_mirror", ctx.accounts.session.key().as_ref()],
+        er_config.delegate_config(),
+    )?;
+
+    emit!(SessionMirrorDelegated {
+        session_id: ctx.accounts.session.session_id,
+        validator: er_config.validator,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
-use crate::{state::TradingSession, AccountsDelegated};
+use crate::{require_undelegated, state::TradingSession, AccountsDelegated};
 use anchor_lang::prelude::*;
-use ephemeral_rollups_sdk::{anchor::delegate, cpi::DelegateConfig};
+use ephemeral_rollups_sdk::anchor::delegate;
 #[delegate]
 #[derive(Accounts)]
 pub struct DelegateTradingAccounts<'info> {
@@ -14,30 +14,34 @@ pub struct DelegateTradingAccounts<'info> {
     #[account(mut, del)]
     pub portfolio: AccountInfo<'info>,
 
-    /// CHECK: Optional validator account (passed as remaining account or in DelegateConfig)
-    pub validator: Option<AccountInfo<'info>>,
-
+    /// CHECK: Will be validated by delegate program
     #[account(mut, del)]
-    pub pda: AccountInfo<'info>,
+    pub trade_history: AccountInfo<'info>,
 }
 
-/// Delegate the portfolio (or multiple PDAs) to an ER validator so they may execute high-frequency updates off-chain
+/// Delegate the portfolio and its trade history to an ER validator so they may execute high-frequency updates off-chain.
+/// The validator and commit frequency come from the session, so all players share one rollup.
 pub fn process_delegate_trading_accounts(ctx: Context<DelegateTradingAccounts>) -> Result<()> {
-    // Use delegate macro helper to perform the CPI into delegation program
-    // Validator optionally passed as remaining account or provided in DelegateConfig
-    ctx.accounts.delegate_pda(
+    require_undelegated(&ctx.accounts.portfolio)?;
+    require_undelegated(&ctx.accounts.trade_history)?;
+    let er_config = &ctx.accounts.session.er_config;
+
+    // Use delegate macro helpers to perform the CPI into delegation program
+    ctx.accounts.delegate_portfolio(
         &ctx.accounts.user,
         &[
             b"portfolio",
             ctx.accounts.session.key().as_ref(),
             ctx.accounts.user.key().as_ref(),
         ],
-        DelegateConfig {
-            // Optionally set a validator pubkey (first remaining account)
-            validator: ctx.remaining_accounts.first().map(|acc| acc.key()),
-            // You may configure auto-commit frequency, etc. via DelegateConfig fields if available
-            ..Default::default()
-        },
+        er_config.delegate_config(),
+    )?;
+
+    // Every fill writes the trade history, so it must live in the same rollup as the portfolio
+    ctx.accounts.delegate_trade_history(
+        &ctx.accounts.user,
+        &[b"trade_history", ctx.accounts.portfolio.key().as_ref()],
+        er_config.delegate_config(),
     )?;
 
     emit!(AccountsDelegated {
@@ -1,29 +1,81 @@
 use anchor_lang::prelude::*;
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
 
 use crate::{
-    calculate_portfolio_value, error::ErrorCode, OrderExecuted, OrderSide, Portfolio, Position,
-    PositionSide, TradingSession,
+    calculate_fill_price, calculate_portfolio_value, calculate_slippage_bps, calculate_taker_fee,
+    check_circuit_breaker, check_price_protection, check_risk_limits, error::ErrorCode,
+    find_trading_pair, load_rollup_portfolio_mut, load_rollup_price_guard_mut,
+    load_rollup_session_mirror, load_rollup_trade_history, read_oracle_price, record_trade,
+    resolve_order_quantity, store_trade_history, Money, OrderAmount, OrderExecuted, OrderSide,
+    Position, PositionClosed, PositionOpened, Price, PriceProtection, Quantity, Rounding,
+    TradeRecord, TradingHalted, ORDER_PRICE_MAX_AGE,
 };
 
 #[derive(Accounts)]
 pub struct ExecuteOrder<'info> {
+    /// CHECK: loaded in the handler after the delegation check, so an order sent to the base
+    /// layer fails with `RequiresEphemeralRollup` instead of Anchor's owner check
     #[account(mut)]
-    pub portfolio: Account<'info, Portfolio>,
-    pub session: Account<'info, TradingSession>,
-    pub price_update: Account<'info, PriceUpdateV2>,
+    pub portfolio: UncheckedAccount<'info>,
+    /// CHECK: loaded in the handler and matched against the portfolio
+    #[account(mut)]
+    pub trade_history: UncheckedAccount<'info>,
+    /// CHECK: session config as synced into the rollup; loaded in the handler
+    pub session_mirror: UncheckedAccount<'info>,
+    /// CHECK: circuit breaker of the traded pair; loaded in the handler
+    #[account(mut)]
+    pub price_guard: UncheckedAccount<'info>,
+    /// CHECK: matched against the traded pair's oracle source in `read_oracle_price`
+    pub price_feed: UncheckedAccount<'info>,
     pub user: Signer<'info>,
 }
 
 pub fn process_execute_market_order(
     ctx: Context<ExecuteOrder>,
-    trading_pair: String,
+    pair_index: u16,
     side: OrderSide,
     quantity: u64,
+    price_protection: Option<PriceProtection>,
+) -> Result<()> {
+    fill_market_order(
+        ctx,
+        pair_index,
+        side,
+        OrderAmount::Quantity(quantity),
+        price_protection,
+    )
+}
+
+/// Market order sized in quote currency, as a share of cash, or closing the whole position.
+/// The base quantity is derived on-chain from the normalized oracle price.
+pub fn process_execute_market_order_by_amount(
+    ctx: Context<ExecuteOrder>,
+    pair_index: u16,
+    side: OrderSide,
+    amount: OrderAmount,
+    price_protection: Option<PriceProtection>,
 ) -> Result<()> {
-    let portfolio = &mut ctx.accounts.portfolio;
-    let session = &ctx.accounts.session;
-    let price_update = &ctx.accounts.price_update;
+    fill_market_order(ctx, pair_index, side, amount, price_protection)
+}
+
+fn fill_market_order(
+    ctx: Context<ExecuteOrder>,
+    pair_index: u16,
+    side: OrderSide,
+    amount: OrderAmount,
+    price_protection: Option<PriceProtection>,
+) -> Result<()> {
+    let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+    let session_key = mirror.session;
+    let session = &mirror.config;
+    let portfolio_info = &ctx.accounts.portfolio;
+    let mut portfolio = load_rollup_portfolio_mut(portfolio_info, &mirror)?;
+    require_keys_eq!(
+        portfolio.owner,
+        ctx.accounts.user.key(),
+        ErrorCode::NotPortfolioOwner
+    );
+    let mut trade_history =
+        load_rollup_trade_history(&ctx.accounts.trade_history, portfolio_info.key)?;
     let clock = Clock::get()?;
     require!(session.is_active, ErrorCode::SessionInactive);
     require!(
@@ -31,178 +83,234 @@ pub fn process_execute_market_order(
         ErrorCode::SessionEnded
     );
 
-    let feed_id = match trading_pair.as_str() {
-        "JUP/USD" => get_feed_id_from_hex(
-            "0x0a0408d619e9380abad35060f9192039ed5042fa6f82301d0e48bb52be830996",
-        )?,
-        "SOL/USD" => get_feed_id_from_hex(
-            "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d",
-        )?,
-        _ => return Err(ErrorCode::UnsupportedTradingPair.into()),
-    };
+    let pair = find_trading_pair(session, pair_index)?;
+    let slot = pair_index as usize;
+    let base_unit = pair.base_unit();
 
-    // Get price from Pyth oracle (max 30 seconds old)
-    let maximum_age: u64 = 30;
-    let price_data = price_update
-        .get_price_no_older_than(&clock, maximum_age, &feed_id)
-        .map_err(|_| ErrorCode::StalePriceData)?;
+    // Price from the pair's oracle in the 6-decimal quote format (1_000_000 = 1.0)
+    let oracle = read_oracle_price(
+        &pair.oracle,
+        Some(&ctx.accounts.price_feed.to_account_info()),
+        &clock,
+        ORDER_PRICE_MAX_AGE,
+    )?;
+    let oracle_price = oracle.price;
 
-    // Convert Pyth price to your 6-decimal format
-    // Pyth typically uses exponent (e.g., exponent=-8 means price is in 10^-8)
-    // We need to normalize to 6 decimals (1_000_000 = 1.0)
-    // let current_price = normalize_price(price_data.price, price_data.exponent)?;
+    let mut guard = load_rollup_price_guard_mut(&ctx.accounts.price_guard, &mirror, pair_index)?;
+    let move_check =
+        check_circuit_breaker(&guard, pair.max_price_move_bps, oracle_price, oracle.publish_time)?;
+    if let Some(move_bps) = move_check {
+        let last_price = guard.last_price;
+        guard.halted = 1;
+        guard.touch()?;
+        emit!(TradingHalted {
+            session_id: session.session_id,
+            pair_index,
+            last_price,
+            price: oracle_price.raw(),
+            move_bps,
+            version: guard.version,
+        });
+        msg!(
+            "Circuit breaker tripped on pair {}: {} -> {} ({} bps); order not filled",
+            pair_index,
+            last_price,
+            oracle_price.raw(),
+            move_bps
+        );
+        // the halt has to persist, so the order is dropped instead of failing the transaction
+        return Ok(());
+    }
 
     msg!(
-        "Pyth price for {}: ({} ± {}) * 10^{} = {} (normalized)",
-        trading_pair,
-        price_data.price,
-        price_data.conf,
-        price_data.exponent,
-        price_data.price
+        "Oracle price for {}: {} (normalized)",
+        pair.symbol,
+        oracle_price.raw()
+    );
+    let quantity = resolve_order_quantity(&amount, &side, &portfolio, session, slot, oracle_price)?;
+    require!(
+        quantity > Quantity::ZERO
+            && (amount == OrderAmount::ClosePosition
+                || quantity.raw().checked_rem(pair.lot_size) == Some(0)),
+        ErrorCode::InvalidLotSize
     );
-    // order_value in the same 6-decimals base
-    let order_value = (quantity as i64)
-        .checked_mul(price_data.price)
-        .ok_or(ErrorCode::MathOverflow)?
-        / 1_000_000;
 
+    // notional at the oracle price drives the slippage curve
+    let oracle_notional = Money::notional(quantity, oracle_price, base_unit, Rounding::Down)?;
+    // a position that shrank below the minimum by partial sells or a price drop must still close
+    let flattens_position =
+        side == OrderSide::Sell && quantity.raw() == portfolio.positions[slot].quantity;
+    require!(
+        flattens_position || oracle_notional >= Money::from_unsigned(pair.min_notional)?,
+        ErrorCode::BelowMinNotional
+    );
+    let slippage_bps = calculate_slippage_bps(&session.fees, oracle_notional);
+    let fill_price = calculate_fill_price(oracle_price, &side, slippage_bps)?;
+    check_price_protection(&price_protection, &side, fill_price)?;
+
+    // the taker pays rounding on both legs: buys round up, sells round down
+    let order_value = match side {
+        OrderSide::Buy => Money::notional(quantity, fill_price, base_unit, Rounding::Up)?,
+        OrderSide::Sell => Money::notional(quantity, fill_price, base_unit, Rounding::Down)?,
+    };
+    let fee = calculate_taker_fee(&session.fees, order_value)?;
+
+    // fees are a realized loss, so they show up in the leaderboard PnL as well as in cash
+    let mut realized_pnl = fee.checked_neg()?;
+    // whole position in the traded pair after a buy, for the per-pair risk limit
+    let mut position_quantity = Quantity::ZERO;
+    let opened_position = side == OrderSide::Buy && portfolio.positions[slot].quantity == 0;
+    let mut closed_position = false;
     match side {
         OrderSide::Buy => {
-            require!(
-                portfolio.cash_balance >= order_value,
-                ErrorCode::InsufficientFunds
-            );
-
-            portfolio.cash_balance = portfolio
-                .cash_balance
-                .checked_sub(order_value)
-                .ok_or(ErrorCode::MathOverflow)?;
-
-            // add or average into existing position
-            if let Some(pos) = portfolio
-                .positions
-                .iter_mut()
-                .find(|p| p.trading_pair == trading_pair)
-            {
-                let total_cost = (pos.quantity as i64)
-                    .checked_mul(pos.avg_entry_price)
-                    .ok_or(ErrorCode::MathOverflow)?
-                    .checked_add(
-                        order_value
-                            .checked_mul(1_000_000)
-                            .ok_or(ErrorCode::MathOverflow)?,
-                    )
-                    .ok_or(ErrorCode::MathOverflow)?;
-
-                pos.quantity = pos
-                    .quantity
-                    .checked_add(quantity)
-                    .ok_or(ErrorCode::MathOverflow)?;
-                // avg_entry_price stored in same 6-decimal basis as price_feed.price
-                pos.avg_entry_price = total_cost
-                    .checked_div(pos.quantity as i64)
-                    .ok_or(ErrorCode::MathOverflow)?;
+            let total_cost = order_value.checked_add(fee)?;
+            let cash_balance = Money::new(portfolio.cash_balance);
+            require!(cash_balance >= total_cost, ErrorCode::InsufficientFunds);
+
+            portfolio.cash_balance = cash_balance.checked_sub(total_cost)?.raw();
+
+            // add or average into the pair's position slot
+            let pos = &mut portfolio.positions[slot];
+            let held = Quantity::new(pos.quantity);
+            // avg_entry_price stored in the same 6-decimal basis as the oracle price
+            pos.avg_entry_price = if held == Quantity::ZERO {
+                fill_price.raw()
             } else {
-                portfolio.positions.push(Position {
-                    trading_pair: trading_pair.clone(),
+                Price::weighted_average(
+                    held,
+                    Price::new(pos.avg_entry_price),
                     quantity,
-                    avg_entry_price: price_data.price,
-                    side: PositionSide::Long,
-                });
-            }
+                    fill_price,
+                    Rounding::Up,
+                )?
+                .raw()
+            };
+            pos.quantity = held.checked_add(quantity)?.raw();
+            position_quantity = Quantity::new(pos.quantity);
         }
         OrderSide::Sell => {
-            let pos_idx = portfolio
-                .positions
-                .iter()
-                .position(|p| p.trading_pair == trading_pair)
-                .ok_or(ErrorCode::NoPosition)?;
-
-            // Read needed fields into locals so we don't hold a mutable borrow across portfolio updates
-            let available_qty = portfolio.positions[pos_idx].quantity;
+            let available_qty = Quantity::new(portfolio.positions[slot].quantity);
+            require!(available_qty > Quantity::ZERO, ErrorCode::NoPosition);
             require!(available_qty >= quantity, ErrorCode::InsufficientPosition);
-            let avg_entry_price = portfolio.positions[pos_idx].avg_entry_price;
+            let avg_entry_price = Price::new(portfolio.positions[slot].avg_entry_price);
 
             // cost basis and proceeds use same decimals
-            let cost_basis = (quantity as i64)
-                .checked_mul(avg_entry_price)
-                .ok_or(ErrorCode::MathOverflow)?
-                / 1_000_000;
-            let sale_proceeds = order_value;
-            let pnl = sale_proceeds
-                .checked_sub(cost_basis)
-                .ok_or(ErrorCode::MathOverflow)?;
-
-            portfolio.realized_pnl = portfolio
-                .realized_pnl
-                .checked_add(pnl)
-                .ok_or(ErrorCode::MathOverflow)?;
-            portfolio.cash_balance = portfolio
-                .cash_balance
-                .checked_add(sale_proceeds)
-                .ok_or(ErrorCode::MathOverflow)?;
-
-            // update or remove position without holding a mutable borrow while removing
-            let new_qty = portfolio.positions[pos_idx]
-                .quantity
-                .checked_sub(quantity)
-                .ok_or(ErrorCode::MathOverflow)?;
-            if new_qty == 0 {
-                portfolio.positions.remove(pos_idx);
-            } else {
-                portfolio.positions[pos_idx].quantity = new_qty;
-            }
+            let cost_basis = Money::notional(quantity, avg_entry_price, base_unit, Rounding::Up)?;
+            let sale_proceeds = order_value.checked_sub(fee)?;
+            realized_pnl = sale_proceeds.checked_sub(cost_basis)?;
+
+            portfolio.cash_balance = Money::new(portfolio.cash_balance)
+                .checked_add(sale_proceeds)?
+                .raw();
+
+            // a fully closed slot is left with zero quantity and entry price
+            let new_qty = available_qty.checked_sub(quantity)?;
+            closed_position = new_qty == Quantity::ZERO;
+            portfolio.positions[slot] = Position {
+                quantity: new_qty.raw(),
+                avg_entry_price: if new_qty == Quantity::ZERO {
+                    0
+                } else {
+                    avg_entry_price.raw()
+                },
+            };
         }
     }
 
+    portfolio.realized_pnl = Money::new(portfolio.realized_pnl)
+        .checked_add(realized_pnl)?
+        .raw();
+    portfolio.fees_paid = portfolio
+        .fees_paid
+        .checked_add(fee.to_unsigned()?)
+        .ok_or(ErrorCode::MathOverflow)?;
     portfolio.num_trades = portfolio
         .num_trades
         .checked_add(1)
         .ok_or(ErrorCode::MathOverflow)?;
 
+    record_trade(
+        &mut trade_history,
+        TradeRecord {
+            pair_index,
+            side: side.clone(),
+            quantity: quantity.raw(),
+            price: fill_price.raw(),
+            timestamp: clock.unix_timestamp,
+            realized_pnl: realized_pnl.raw(),
+            fee: fee.to_unsigned()?,
+        },
+    );
+    store_trade_history(&ctx.accounts.trade_history, &trade_history)?;
+
     // Recalculate total portfolio value using price feeds passed as remaining accounts
-    calculate_portfolio_value(portfolio, &ctx.remaining_accounts)?;
+    calculate_portfolio_value(&mut portfolio, session, &session_key, ctx.remaining_accounts)?;
+
+    // only orders that add risk are limited; sells are always allowed to de-risk
+    if side == OrderSide::Buy {
+        let position_notional =
+            Money::notional(position_quantity, fill_price, base_unit, Rounding::Up)?;
+        check_risk_limits(&session.risk_limits, &portfolio, position_notional)?;
+    }
+    portfolio.touch()?;
 
     msg!(
-        "Executed {:?} order: {} qty @ {} for user {}",
+        "Executed {:?} order: {} qty @ {} (oracle {}, slippage {} bps, fee {}) for user {}",
         side,
-        quantity,
-        price_data.price,
+        quantity.raw(),
+        fill_price.raw(),
+        oracle_price.raw(),
+        slippage_bps,
+        fee.raw(),
         portfolio.owner
     );
 
+    let position = &portfolio.positions[slot];
     emit!(OrderExecuted {
+        session_id: session.session_id,
         user: portfolio.owner,
-        trading_pair,
+        pair_index,
         side,
-        quantity,
-        price: price_data.price,
+        quantity: quantity.raw(),
+        price: fill_price.raw(),
+        oracle_price: oracle_price.raw(),
+        slippage_bps,
+        fee: fee.to_unsigned()?,
+        realized_pnl: realized_pnl.raw(),
+        position_quantity: position.quantity,
+        avg_entry_price: position.avg_entry_price,
+        cash_balance: portfolio.cash_balance,
+        total_value: portfolio.total_value,
         timestamp: clock.unix_timestamp,
+        version: portfolio.version,
     });
+    if opened_position {
+        emit!(PositionOpened {
+            session_id: session.session_id,
+            user: portfolio.owner,
+            pair_index,
+            quantity: position.quantity,
+            entry_price: position.avg_entry_price,
+            timestamp: clock.unix_timestamp,
+            version: portfolio.version,
+        });
+    }
+    if closed_position {
+        emit!(PositionClosed {
+            session_id: session.session_id,
+            user: portfolio.owner,
+            pair_index,
+            quantity: quantity.raw(),
+            exit_price: fill_price.raw(),
+            realized_pnl: realized_pnl.raw(),
+            timestamp: clock.unix_timestamp,
+            version: portfolio.version,
+        });
+    }
+
+    // the fill becomes the reference for the next move check
+    guard.last_price = oracle_price.raw();
+    guard.last_publish_time = oracle.publish_time;
 
     Ok(())
 }
-
-// /// Normalize Pyth price to 6 decimals (1_000_000 = 1.0)
-// fn normalize_price(price: i64, exponent: i32) -> Result<i64> {
-//     // Pyth exponent is typically negative (e.g., -8 means 10^-8)
-//     // Target exponent is -6 (for 6 decimals)
-//     let target_exponent: i32 = -6;
-//     let exponent_diff = target_exponent - exponent;
-
-//     let normalized = if exponent_diff > 0 {
-//         // Need to multiply (shift decimal right)
-//         price
-//             .checked_mul(10_i64.pow(exponent_diff as u32))
-//             .ok_or(ErrorCode::MathOverflow)?
-//     } else if exponent_diff < 0 {
-//         // Need to divide (shift decimal left)
-//         price
-//             .checked_div(10_i64.pow((-exponent_diff) as u32))
-//             .ok_or(ErrorCode::MathOverflow)?
-//     } else {
-//         price
-//     };
-
-//     Ok(normalized)
-// }
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
+
+use crate::{
+    error::ErrorCode, load_rollup_leaderboard_mut, load_rollup_session_mirror,
+    load_session_portfolio, require_in_rollup, SessionAccountsFinalized, TradeHistory,
+};
+
+#[derive(Accounts)]
+pub struct FinalizeSessionAccounts<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: loaded in the handler after the delegation check
+    #[account(mut)]
+    pub session_mirror: UncheckedAccount<'info>,
+
+    /// CHECK: loaded in the handler after the delegation check; only passed when the session
+    /// delegates its leaderboard to the rollup
+    #[account(mut)]
+    pub leaderboard: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Magic ER context
+    #[account(mut)]
+    pub magic_context: AccountInfo<'info>,
+
+    /// CHECK: ER program account
+    pub magic_program: AccountInfo<'info>,
+}
+
+/// Keeper crank: once the session has ended, commit and undelegate a batch of players'
+/// accounts without their signatures, so the session can settle while players are offline.
+///
+/// Remaining accounts: `portfolio_count` pairs of writable `[portfolio, trade_history]`.
+/// Pass the leaderboard with the last batch of a session that delegates it, and
+/// `include_session_mirror` on the last batch to bring the session mirror back as well.
+/// Intended to be cranked inside the ephemeral rollup.
+pub fn process_finalize_session_accounts<'info>(
+    ctx: Context<'_, '_, 'info, 'info, FinalizeSessionAccounts<'info>>,
+    portfolio_count: u8,
+    include_session_mirror: bool,
+) -> Result<()> {
+    let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+    let session = &mirror.config;
+    let session_key = mirror.session;
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp >= session.end_time,
+        ErrorCode::SessionStillActive
+    );
+    let include_leaderboard = ctx.accounts.leaderboard.is_some();
+    require!(
+        !include_leaderboard || session.er_config.delegate_leaderboard,
+        ErrorCode::LeaderboardDelegationDisabled
+    );
+
+    let portfolio_count = portfolio_count as usize;
+    require!(
+        ctx.remaining_accounts.len() == portfolio_count * 2
+            && (portfolio_count > 0 || include_leaderboard || include_session_mirror),
+        ErrorCode::InvalidBatchSize
+    );
+
+    let mut accounts: Vec<&AccountInfo<'info>> = Vec::with_capacity(portfolio_count * 2 + 2);
+    for pair in ctx.remaining_accounts.chunks_exact(2) {
+        let (portfolio_info, trade_history_info) = (&pair[0], &pair[1]);
+        require!(
+            portfolio_info.is_writable && trade_history_info.is_writable,
+            ErrorCode::PortfolioNotWritable
+        );
+        require_in_rollup(portfolio_info)?;
+        require_in_rollup(trade_history_info)?;
+        let loader = load_session_portfolio(portfolio_info, &session_key, session.session_id)?;
+        {
+            let mut portfolio = loader.load_mut()?;
+            portfolio.committed_version = portfolio.version;
+        }
+        // only this program creates trade histories, always at the portfolio's PDA
+        let trade_history = Account::<TradeHistory>::try_from(trade_history_info)?;
+        require_keys_eq!(
+            trade_history.portfolio,
+            portfolio_info.key(),
+            ErrorCode::SessionMismatch
+        );
+        accounts.push(portfolio_info);
+        accounts.push(trade_history_info);
+    }
+
+    let leaderboard = ctx.accounts.leaderboard.as_ref().map(|l| l.to_account_info());
+    if let Some(leaderboard) = &leaderboard {
+        {
+            let mut leaderboard_data = load_rollup_leaderboard_mut(leaderboard, &mirror)?;
+            leaderboard_data.committed_version = leaderboard_data.version;
+        }
+        accounts.push(leaderboard);
+    }
+    let session_mirror = ctx.accounts.session_mirror.to_account_info();
+    if include_session_mirror {
+        accounts.push(&session_mirror);
+    }
+
+    commit_and_undelegate_accounts(
+        &ctx.accounts.payer,
+        accounts,
+        &ctx.accounts.magic_context,
+        &ctx.accounts.magic_program,
+    )?;
+
+    emit!(SessionAccountsFinalized {
+        session_id: session.session_id,
+        portfolio_count: portfolio_count as u8,
+        leaderboard: include_leaderboard,
+        session_mirror: include_session_mirror,
+    });
+
+    msg!(
+        "Finalized {} portfolios of session {}",
+        portfolio_count,
+        session.session_id
+    );
+    Ok(())
+}
@@ -1,21 +1,31 @@
 use anchor_lang::prelude::*;
 use ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts;
 
-use crate::{AccountsFinalized, Leaderboard, Portfolio, TradingSession};
+use crate::{
+    error::ErrorCode, load_rollup_leaderboard_mut, load_rollup_portfolio_mut,
+    load_rollup_session_mirror, load_rollup_trade_history, AccountsFinalized,
+};
 
 #[derive(Accounts)]
 pub struct FinalizeTradingAccounts<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// CHECK: loaded in the handler after the delegation check
+    pub session_mirror: UncheckedAccount<'info>,
+
+    /// CHECK: loaded in the handler after the delegation check and matched against `user`
     #[account(mut)]
-    pub session: Account<'info, TradingSession>,
+    pub portfolio: UncheckedAccount<'info>,
 
+    /// CHECK: loaded in the handler after the delegation check; only passed when the session
+    /// delegates its leaderboard to the rollup
     #[account(mut)]
-    pub portfolio: Account<'info, Portfolio>,
+    pub leaderboard: Option<UncheckedAccount<'info>>,
 
+    /// CHECK: loaded in the handler and matched against the portfolio
     #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
+    pub trade_history: UncheckedAccount<'info>,
 
     /// CHECK: Magic ER context
     #[account(mut)]
@@ -28,19 +38,46 @@ pub struct FinalizeTradingAccounts<'info> {
 
  /// Finalize: commit final state and undelegate the accounts back to the program
     pub fn process_finalize_and_undelegate(ctx: Context<FinalizeTradingAccounts>) -> Result<()> {
+        let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+        let portfolio = ctx.accounts.portfolio.to_account_info();
+        let leaderboard = ctx.accounts.leaderboard.as_ref().map(|l| l.to_account_info());
+        let trade_history = ctx.accounts.trade_history.to_account_info();
+        load_rollup_trade_history(&trade_history, portfolio.key)?;
+        let mut accounts = vec![&portfolio, &trade_history];
+        let portfolio_version = {
+            let mut portfolio_data = load_rollup_portfolio_mut(&portfolio, &mirror)?;
+            require_keys_eq!(
+                portfolio_data.owner,
+                ctx.accounts.user.key(),
+                ErrorCode::NotPortfolioOwner
+            );
+            portfolio_data.committed_version = portfolio_data.version;
+            portfolio_data.version
+        };
+        // the leaderboard only lives in the rollup when the session delegates it
+        let mut leaderboard_version = None;
+        if let Some(leaderboard) = &leaderboard {
+            require!(
+                mirror.config.er_config.delegate_leaderboard,
+                ErrorCode::LeaderboardDelegationDisabled
+            );
+            let mut leaderboard_data = load_rollup_leaderboard_mut(leaderboard, &mirror)?;
+            leaderboard_data.committed_version = leaderboard_data.version;
+            leaderboard_version = Some(leaderboard_data.version);
+            accounts.push(leaderboard);
+        }
         commit_and_undelegate_accounts(
             &ctx.accounts.user,
-            vec![
-                &ctx.accounts.portfolio.to_account_info(),
-                &ctx.accounts.leaderboard.to_account_info(),
-            ],
+            accounts,
             &ctx.accounts.magic_context,
             &ctx.accounts.magic_program,
         )?;
 
         emit!(AccountsFinalized {
-            session_id: ctx.accounts.session.session_id,
+            session_id: mirror.config.session_id,
             user: ctx.accounts.user.key(),
+            portfolio_version,
+            leaderboard_version,
         });
 
         msg!("Finalized and undelegated accounts for user {}", ctx.accounts.user.key());
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    calculate_settlement_twap, check_price_accounts, error::ErrorCode, find_price_twap,
+    find_trading_pair, read_mark_price, PriceMode, SettlementPrice, SettlementPriceFixed,
+    TradingSession, SETTLEMENT_GRACE_PERIOD, SETTLEMENT_PRICE_WINDOW,
+};
+
+#[derive(Accounts)]
+pub struct FixSettlementPrices<'info> {
+    #[account(mut)]
+    pub session: Account<'info, TradingSession>,
+    /// Only for the override after `SETTLEMENT_GRACE_PERIOD`
+    pub authority: Option<Signer<'info>>,
+}
+
+/// Permissionless: fix the settlement price of each listed pair from the price accounts passed
+/// as remaining accounts, read in the session's settlement price mode. Only a price published
+/// within `SETTLEMENT_PRICE_WINDOW` seconds after `end_time` is accepted, which leaves the caller
+/// no room to pick a favourable one, and every player is settled at the same marks. In TWAP
+/// mode the average is taken over the window before `end_time`, whenever this runs.
+///
+/// Feeds only keep their latest price, so a pair nobody fixed in time would block settlement.
+/// Signed by the session authority after `SETTLEMENT_GRACE_PERIOD`, this instead fixes each
+/// listed pair from any spot price published at or after `end_time`.
+pub fn process_fix_settlement_prices(
+    ctx: Context<FixSettlementPrices>,
+    pair_indexes: Vec<u16>,
+) -> Result<()> {
+    let session_key = ctx.accounts.session.key();
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp >= session.end_time,
+        ErrorCode::SessionStillActive
+    );
+    require!(!session.is_settled, ErrorCode::SessionAlreadySettled);
+    check_price_accounts(ctx.remaining_accounts)?;
+    let authority_override = match &ctx.accounts.authority {
+        Some(authority) => {
+            require_keys_eq!(authority.key(), session.authority, ErrorCode::Unauthorized);
+            let grace_end = session
+                .end_time
+                .checked_add(SETTLEMENT_GRACE_PERIOD)
+                .ok_or(ErrorCode::MathOverflow)?;
+            require!(
+                clock.unix_timestamp >= grace_end,
+                ErrorCode::SettlementGracePeriodActive
+            );
+            true
+        }
+        None => false,
+    };
+    let mode = if authority_override {
+        PriceMode::Spot
+    } else {
+        session.valuation.settlement
+    };
+    let window_end = session
+        .end_time
+        .checked_add(SETTLEMENT_PRICE_WINDOW)
+        .ok_or(ErrorCode::MathOverflow)?;
+    // the window bounds the price, so a posted update from the window stays usable later on
+    let maximum_age = clock
+        .unix_timestamp
+        .checked_sub(session.end_time)
+        .ok_or(ErrorCode::MathOverflow)? as u64;
+
+    // one config change for the whole batch; the events carry its version
+    session.touch_config()?;
+    for pair_index in pair_indexes {
+        let pair = find_trading_pair(session, pair_index)?;
+        require!(
+            !session.settlement_prices[pair_index as usize].is_fixed(),
+            ErrorCode::SettlementPriceAlreadyFixed
+        );
+
+        let oracle_price = if mode == PriceMode::Twap {
+            let Some(twap) = find_price_twap(&session_key, pair_index, ctx.remaining_accounts)?
+            else {
+                msg!("No TWAP account passed for {}", pair.symbol);
+                return Err(ErrorCode::PriceFeedNotFound.into());
+            };
+            calculate_settlement_twap(&twap, session.end_time)?
+        } else {
+            let oracle_price = read_mark_price(
+                pair,
+                pair_index,
+                mode,
+                &session_key,
+                ctx.remaining_accounts,
+                &clock,
+                maximum_age,
+            )?;
+            require!(
+                oracle_price.publish_time >= session.end_time,
+                ErrorCode::SettlementPriceTooEarly
+            );
+            require!(
+                authority_override || oracle_price.publish_time <= window_end,
+                ErrorCode::SettlementPriceTooLate
+            );
+            oracle_price
+        };
+        require!(oracle_price.price.raw() > 0, ErrorCode::InvalidPriceData);
+
+        session.settlement_prices[pair_index as usize] = SettlementPrice {
+            price: oracle_price.price.raw(),
+            publish_time: oracle_price.publish_time,
+        };
+
+        emit!(SettlementPriceFixed {
+            session_id: session.session_id,
+            pair_index,
+            price: oracle_price.price.raw(),
+            publish_time: oracle_price.publish_time,
+            authority_override,
+            version: session.config_version,
+        });
+    }
+
+    Ok(())
+}
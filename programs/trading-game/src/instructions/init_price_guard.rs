@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::{find_trading_pair, PriceGuard, TradingSession};
+
+#[derive(Accounts)]
+#[instruction(pair_index: u16)]
+pub struct InitPriceGuard<'info> {
+    pub session: Account<'info, TradingSession>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + PriceGuard::INIT_SPACE,
+        seeds = [b"price_guard", session.key().as_ref(), &pair_index.to_le_bytes()],
+        bump
+    )]
+    pub price_guard: AccountLoader<'info, PriceGuard>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless: create the circuit breaker account of a pair. Orders on the pair need it,
+/// so it is created once per listed pair, before the pair is traded.
+pub fn process_init_price_guard(ctx: Context<InitPriceGuard>, pair_index: u16) -> Result<()> {
+    find_trading_pair(&ctx.accounts.session, pair_index)?;
+
+    let mut price_guard = ctx.accounts.price_guard.load_init()?;
+    price_guard.session = ctx.accounts.session.key();
+    price_guard.pair_index = pair_index;
+    price_guard.bump = ctx.bumps.price_guard;
+
+    Ok(())
+}
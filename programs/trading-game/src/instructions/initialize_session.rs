@@ -1,24 +1,46 @@
 use anchor_lang::prelude::*;
 
-use crate::{SessionInitialized, TradingSession};
+use crate::{
+    validate_trading_pairs, ErConfig, FeeConfig, Leaderboard, RiskLimits,
+    SessionInitialized, SessionMirror, SettlementPrice, TradingPair, TradingSession,
+    ValuationConfig, MAX_TRADING_PAIRS,
+};
 
 #[derive(Accounts)]
+#[instruction(session_id: u64, duration_seconds: i64, virtual_balance: u64, trading_pairs: Vec<TradingPair>)]
 pub struct InitializeSession<'info> {
-    #[account(init, payer = authority, space = 8 + TradingSession::INIT_SPACE)]
+    // clamped so an oversized list reaches `validate_trading_pairs` instead of failing allocation
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TradingSession::space(trading_pairs.len().min(MAX_TRADING_PAIRS))
+    )]
     pub session: Account<'info, TradingSession>,
+    #[account(init, payer = authority, space = 8 + Leaderboard::INIT_SPACE, seeds = [b"leaderboard", session.key().as_ref()], bump)]
+    pub leaderboard: AccountLoader<'info, Leaderboard>,
+    #[account(init, payer = authority, space = 8 + SessionMirror::INIT_SPACE, seeds = [b"session_mirror", session.key().as_ref()], bump)]
+    pub session_mirror: Account<'info, SessionMirror>,
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
  // ----------------------------
+    #[allow(clippy::too_many_arguments)]
     pub fn process_initialize_session(
         ctx: Context<InitializeSession>,
         session_id: u64,
         duration_seconds: i64,
         virtual_balance: u64,
-        trading_pairs: Vec<String>,
+        trading_pairs: Vec<TradingPair>,
+        fees: FeeConfig,
+        risk_limits: RiskLimits,
+        valuation: ValuationConfig,
+        er_config: ErConfig,
     ) -> Result<()> {
+        validate_trading_pairs(&trading_pairs)?;
+        fees.validate()?;
+
         let session = &mut ctx.accounts.session;
         let clock = Clock::get()?;
 
@@ -29,11 +51,37 @@ pub struct InitializeSession<'info> {
         session.trading_pairs = trading_pairs;
         session.is_active = true;
         session.participant_count = 0;
+        session.is_settled = false;
+        session.fees = fees;
+        session.risk_limits = risk_limits;
+        session.authority = ctx.accounts.authority.key();
+        session.valuation = valuation;
+        session.er_config = er_config;
+        session.settlement_prices = vec![SettlementPrice::default(); session.trading_pairs.len()];
+        session.config_version = 0;
+
+        let session_mirror = &mut ctx.accounts.session_mirror;
+        session_mirror.session = session.key();
+        session_mirror.synced_at = clock.unix_timestamp;
+        session_mirror.bump = ctx.bumps.session_mirror;
+        session_mirror.config = (**session).clone();
+
+        let mut leaderboard = ctx.accounts.leaderboard.load_init()?;
+        leaderboard.session_id = session_id;
+        leaderboard.bump = ctx.bumps.leaderboard;
 
-        emit!(SessionInitialized{
+        emit!(SessionInitialized {
             session_id,
+            authority: session.authority,
             start_time: session.start_time,
             end_time: session.end_time,
+            virtual_balance,
+            trading_pairs: session.trading_pairs.clone(),
+            fees: session.fees.clone(),
+            risk_limits: session.risk_limits.clone(),
+            valuation: session.valuation.clone(),
+            er_config: session.er_config.clone(),
+            version: session.config_version,
         });
 
         msg!("Session {} initialized for {} seconds", session_id, duration_seconds);
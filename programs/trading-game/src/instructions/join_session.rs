@@ -1,13 +1,18 @@
 use anchor_lang::prelude::*;
 
-use crate::{error::ErrorCode, ParticipantJoined, Portfolio, TradingSession};
+use crate::{
+    error::ErrorCode, EquitySample, ParticipantJoined, Portfolio, TradeHistory, TradingSession,
+    MAX_LEADERBOARD_ENTRIES,
+};
 #[derive(Accounts)]
 pub struct JoinSession<'info> {
     // #[account(mut, has_one = session)]
     #[account(mut)]
     pub session: Account<'info, TradingSession>,
     #[account(init, payer = user, space = 8 + Portfolio::INIT_SPACE, seeds = [b"portfolio", session.key().as_ref(), user.key().as_ref()], bump)]
-    pub portfolio: Account<'info, Portfolio>,
+    pub portfolio: AccountLoader<'info, Portfolio>,
+    #[account(init, payer = user, space = 8 + TradeHistory::INIT_SPACE, seeds = [b"trade_history", portfolio.key().as_ref()], bump)]
+    pub trade_history: Account<'info, TradeHistory>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -15,20 +20,45 @@ pub struct JoinSession<'info> {
 
  pub fn process_join_session(ctx: Context<JoinSession>, session_id: u64) -> Result<()> {
         let session = &mut ctx.accounts.session;
-        let portfolio = &mut ctx.accounts.portfolio;
+        let mut portfolio = ctx.accounts.portfolio.load_init()?;
 
         require!(session.is_active, ErrorCode::SessionInactive);
+        // settlement only accepts portfolios carrying the session's own id
+        require!(session_id == session.session_id, ErrorCode::SessionMismatch);
+        // every participant needs a leaderboard slot
+        require!(
+            (session.participant_count as usize) < MAX_LEADERBOARD_ENTRIES,
+            ErrorCode::SessionFull
+        );
         let clock = Clock::get()?;
         require!(clock.unix_timestamp < session.end_time, ErrorCode::SessionEnded);
 
         portfolio.owner = ctx.accounts.user.key();
-        portfolio.session_id = session_id;
+        portfolio.session_id = session.session_id;
         portfolio.cash_balance = session.virtual_balance_per_user as i64;
         portfolio.total_value = session.virtual_balance_per_user as i64;
         portfolio.unrealized_pnl = 0;
         portfolio.realized_pnl = 0;
         portfolio.num_trades = 0;
-        portfolio.positions = vec![];
+        portfolio.bump = ctx.bumps.portfolio;
+        portfolio.fees_paid = 0;
+        portfolio.initial_balance = session.virtual_balance_per_user;
+        portfolio.last_mark_time = clock.unix_timestamp;
+        portfolio.high_water_mark = portfolio.total_value;
+        portfolio.max_drawdown_bps = 0;
+        portfolio.equity_head = 0;
+        portfolio.equity_count = 1;
+        portfolio.equity_samples[0] = EquitySample {
+            timestamp: clock.unix_timestamp,
+            total_value: portfolio.total_value,
+        };
+
+        let trade_history = &mut ctx.accounts.trade_history;
+        trade_history.portfolio = ctx.accounts.portfolio.key();
+        trade_history.head = 0;
+        trade_history.total_recorded = 0;
+        trade_history.records = vec![];
+        trade_history.bump = ctx.bumps.trade_history;
 
         session.participant_count = session.participant_count.saturating_add(1);
 
@@ -36,6 +66,8 @@ pub struct JoinSession<'info> {
             session_id,
             user: portfolio.owner,
             initial_balance: session.virtual_balance_per_user,
+            participant_count: session.participant_count,
+            version: portfolio.version,
         });
 
         msg!(
@@ -1,3 +1,21 @@
+// Where each instruction runs:
+// - base layer: initialize_session, add_trading_pairs, update_session_config, set_manual_price,
+//   set_mock_price, join_session, delegate_trading_accounts, delegate_leaderboard,
+//   delegate_session_mirror, init_price_guard, delegate_price_guard, record_price_sample,
+//   fix_settlement_prices, close_session, start_settlement, settle_portfolios, settle_session
+// - ephemeral rollup: execute_market_order(_by_amount), update_pnl, update_leaderboard(_batch),
+//   checkpoint_accounts, finalize_trading_accounts, finalize_session_accounts
+// - wherever the session mirror lives: sync_session_mirror
+// - wherever the pair's price guard lives: resume_trading
+// Rollup instructions read the session from its mirror; base-layer changes to the session
+// reach them once sync_session_mirror has run.
+// Base-layer instructions that touch delegatable accounts fail with `AccountDelegated` while
+// those accounts are in the rollup. Rollup instructions take every delegatable account unchecked
+// and load it in the handler (helpers/rollup_accounts.rs), so sent to the base layer while those
+// accounts are delegated they fail with `RequiresEphemeralRollup`.
+// Nothing detects a rollup instruction running on the base layer with undelegated accounts:
+// update_pnl and order execution work there for portfolios that were never delegated.
+
 pub mod initialize_session;
 pub use initialize_session::*;
 
@@ -15,6 +33,9 @@ pub use finalize_trading_accounts::*;
 pub mod update_leaderboard;
 pub use update_leaderboard::*;
 
+pub mod update_leaderboard_batch;
+pub use update_leaderboard_batch::*;
+
 pub mod update_pnl;
 pub use update_pnl::*;
 
@@ -26,4 +47,52 @@ pub use join_session::*;
 
 
 pub mod close_session;
-pub use close_session::*;
\ No newline at end of file
+pub use close_session::*;
+
+pub mod start_settlement;
+pub use start_settlement::*;
+
+pub mod settle_portfolios;
+pub use settle_portfolios::*;
+
+pub mod settle_session;
+pub use settle_session::*;
+
+pub mod add_trading_pairs;
+pub use add_trading_pairs::*;
+
+pub mod set_manual_price;
+pub use set_manual_price::*;
+
+pub mod set_mock_price;
+pub use set_mock_price::*;
+
+pub mod record_price_sample;
+pub use record_price_sample::*;
+
+pub mod fix_settlement_prices;
+pub use fix_settlement_prices::*;
+
+pub mod resume_trading;
+pub use resume_trading::*;
+
+pub mod delegate_leaderboard;
+pub use delegate_leaderboard::*;
+
+pub mod finalize_session_accounts;
+pub use finalize_session_accounts::*;
+
+pub mod update_session_config;
+pub use update_session_config::*;
+
+pub mod delegate_session_mirror;
+pub use delegate_session_mirror::*;
+
+pub mod sync_session_mirror;
+pub use sync_session_mirror::*;
+
+pub mod init_price_guard;
+pub use init_price_guard::*;
+
+pub mod delegate_price_guard;
+pub use delegate_price_guard::*;
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    find_trading_pair, latest_price_sample, push_price_sample, read_oracle_price, PriceSample, PriceSampled, PriceTwap,
+    TradingSession, ORDER_PRICE_MAX_AGE,
+};
+
+#[derive(Accounts)]
+#[instruction(pair_index: u16)]
+pub struct RecordPriceSample<'info> {
+    pub session: Account<'info, TradingSession>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PriceTwap::INIT_SPACE,
+        seeds = [b"twap", session.key().as_ref(), &pair_index.to_le_bytes()],
+        bump
+    )]
+    pub price_twap: Account<'info, PriceTwap>,
+    /// CHECK: matched against the pair's oracle source in `read_oracle_price`
+    pub price_feed: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless crank adding the pair's current oracle price to its TWAP samples.
+/// A price published less than `TWAP_SAMPLE_INTERVAL` seconds after the latest sample is skipped,
+/// and the samples stop changing once one was published at or after the session's end, so the
+/// settlement TWAP stays readable.
+pub fn process_record_price_sample(ctx: Context<RecordPriceSample>, pair_index: u16) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let clock = Clock::get()?;
+    let pair = find_trading_pair(session, pair_index)?;
+    if latest_price_sample(&ctx.accounts.price_twap)
+        .is_some_and(|latest| latest.publish_time >= session.end_time)
+    {
+        msg!("TWAP of {} already covers the session end; skipped", pair.symbol);
+        return Ok(());
+    }
+
+    let oracle_price = read_oracle_price(
+        &pair.oracle,
+        Some(&ctx.accounts.price_feed.to_account_info()),
+        &clock,
+        ORDER_PRICE_MAX_AGE,
+    )?;
+
+    let price_twap = &mut ctx.accounts.price_twap;
+    price_twap.session = session.key();
+    price_twap.pair_index = pair_index;
+    price_twap.bump = ctx.bumps.price_twap;
+
+    let recorded = push_price_sample(
+        price_twap,
+        PriceSample {
+            price: oracle_price.price.raw(),
+            publish_time: oracle_price.publish_time,
+        },
+    );
+    if recorded {
+        emit!(PriceSampled {
+            session_id: session.session_id,
+            pair_index,
+            price: oracle_price.price.raw(),
+            publish_time: oracle_price.publish_time,
+        });
+    } else {
+        msg!("Price for {} too close to the latest sample; skipped", pair.symbol);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, find_trading_pair, PriceGuard, TradingResumed, TradingSession};
+
+#[derive(Accounts)]
+#[instruction(pair_index: u16)]
+pub struct ResumeTrading<'info> {
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+    #[account(
+        mut,
+        seeds = [b"price_guard", session.key().as_ref(), &pair_index.to_le_bytes()],
+        bump = price_guard.load()?.bump
+    )]
+    pub price_guard: AccountLoader<'info, PriceGuard>,
+    pub authority: Signer<'info>,
+}
+
+/// Lift a circuit breaker halt. The next fill sets a fresh reference price.
+/// Sent wherever the pair's price guard currently lives.
+pub fn process_resume_trading(ctx: Context<ResumeTrading>, pair_index: u16) -> Result<()> {
+    find_trading_pair(&ctx.accounts.session, pair_index)?;
+    let mut price_guard = ctx.accounts.price_guard.load_mut()?;
+    price_guard.last_price = 0;
+    price_guard.last_publish_time = 0;
+    price_guard.halted = 0;
+    price_guard.touch()?;
+
+    emit!(TradingResumed {
+        session_id: ctx.accounts.session.session_id,
+        pair_index,
+        version: price_guard.version,
+    });
+
+    Ok(())
+}
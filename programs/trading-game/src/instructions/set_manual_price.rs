@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, find_trading_pair, ManualPriceSet, OracleSource, TradingSession};
+
+#[derive(Accounts)]
+pub struct SetManualPrice<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+    pub authority: Signer<'info>,
+}
+
+/// Publish a new price (6 decimals) for a pair whose oracle is `OracleSource::Manual`.
+pub fn process_set_manual_price(
+    ctx: Context<SetManualPrice>,
+    pair_index: u16,
+    price: i64,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    require!(price > 0, ErrorCode::InvalidPriceData);
+
+    // validate the index before borrowing the pair mutably
+    find_trading_pair(session, pair_index)?;
+    let pair = &mut session.trading_pairs[pair_index as usize];
+    require!(
+        matches!(pair.oracle, OracleSource::Manual { .. }),
+        ErrorCode::NotManualOracle
+    );
+    pair.oracle = OracleSource::Manual {
+        price,
+        updated_at: clock.unix_timestamp,
+    };
+    session.touch_config()?;
+
+    emit!(ManualPriceSet {
+        session_id: session.session_id,
+        pair_index,
+        price,
+        updated_at: clock.unix_timestamp,
+        version: session.config_version,
+    });
+
+    Ok(())
+}
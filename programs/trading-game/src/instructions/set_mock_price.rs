@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, find_trading_pair, MockPriceFeed, MockPriceSet, OracleSource, TradingSession,
+};
+
+#[derive(Accounts)]
+#[instruction(pair_index: u16)]
+pub struct SetMockPrice<'info> {
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MockPriceFeed::INIT_SPACE,
+        seeds = [b"mock_price", session.key().as_ref(), &pair_index.to_le_bytes()],
+        bump
+    )]
+    pub mock_price_feed: Account<'info, MockPriceFeed>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Script the price of a pair backed by a mock feed. `publish_time` defaults to now and can be
+/// set explicitly to make a test deterministic or to simulate a stale feed.
+pub fn process_set_mock_price(
+    ctx: Context<SetMockPrice>,
+    pair_index: u16,
+    price: i64,
+    publish_time: Option<i64>,
+) -> Result<()> {
+    require!(cfg!(feature = "mock-oracle"), ErrorCode::MockOracleDisabled);
+    require!(price > 0, ErrorCode::InvalidPriceData);
+
+    let session = &ctx.accounts.session;
+    let pair = find_trading_pair(session, pair_index)?;
+    require!(
+        pair.oracle
+            == OracleSource::Mock {
+                feed: ctx.accounts.mock_price_feed.key()
+            },
+        ErrorCode::OracleAccountMismatch
+    );
+
+    let mock_price_feed = &mut ctx.accounts.mock_price_feed;
+    mock_price_feed.session = session.key();
+    mock_price_feed.pair_index = pair_index;
+    mock_price_feed.price = price;
+    mock_price_feed.publish_time = publish_time.unwrap_or(Clock::get()?.unix_timestamp);
+    mock_price_feed.bump = ctx.bumps.mock_price_feed;
+
+    emit!(MockPriceSet {
+        session_id: session.session_id,
+        pair_index,
+        price,
+        publish_time: mock_price_feed.publish_time,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    calculate_roi, error::ErrorCode, load_session_portfolio, require_undelegated, FinalStandings,
+    PlayerSettled, StandingEntry, TradingSession,
+};
+
+#[derive(Accounts)]
+#[instruction(portfolio_count: u8)]
+pub struct SettlePortfolios<'info> {
+    pub session: Account<'info, TradingSession>,
+    #[account(
+        mut,
+        seeds = [b"standings", session.key().as_ref()],
+        bump = standings.bump,
+        realloc = 8 + FinalStandings::space(standings.entries.len() + portfolio_count as usize),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub standings: Account<'info, FinalStandings>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Add the final results of a batch of portfolios to the session's standings. Each portfolio
+/// must be back on the base layer, marked at or after `end_time`, and not settled before.
+///
+/// Remaining accounts: `portfolio_count` portfolios of the session.
+pub fn process_settle_portfolios<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettlePortfolios<'info>>,
+    portfolio_count: u8,
+) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp >= session.end_time, ErrorCode::SessionStillActive);
+    require!(!session.is_settled, ErrorCode::SessionAlreadySettled);
+    require!(
+        portfolio_count > 0 && ctx.remaining_accounts.len() == portfolio_count as usize,
+        ErrorCode::InvalidBatchSize
+    );
+
+    let session_key = session.key();
+    let standings = &mut ctx.accounts.standings;
+    for account_info in ctx.remaining_accounts {
+        require_undelegated(account_info)?;
+        let loader = load_session_portfolio(account_info, &session_key, session.session_id)?;
+        let portfolio = loader.load()?;
+        require!(
+            portfolio.last_mark_time >= session.end_time,
+            ErrorCode::PortfolioNotMarked
+        );
+        // a portfolio is the owner's PDA for this session, so one entry per owner means one
+        // entry per portfolio
+        require!(
+            !standings.entries.iter().any(|e| e.user == portfolio.owner),
+            ErrorCode::IncompleteParticipants
+        );
+
+        let entry = StandingEntry {
+            user: portfolio.owner,
+            total_value: portfolio.total_value,
+            total_pnl: portfolio
+                .realized_pnl
+                .checked_add(portfolio.unrealized_pnl)
+                .ok_or(ErrorCode::MathOverflow)?,
+            roi_percentage: calculate_roi(&portfolio)?,
+            num_trades: portfolio.num_trades,
+            rank: 0,
+            max_drawdown_bps: portfolio.max_drawdown_bps,
+        };
+        emit!(PlayerSettled {
+            session_id: session.session_id,
+            user: entry.user,
+            total_value: entry.total_value,
+            total_pnl: entry.total_pnl,
+            roi_percentage: entry.roi_percentage,
+            num_trades: entry.num_trades,
+            max_drawdown_bps: entry.max_drawdown_bps,
+            version: session.config_version,
+        });
+        standings.entries.push(entry);
+    }
+
+    msg!(
+        "Settled {} of {} participants of session {}",
+        standings.entries.len(),
+        session.participant_count,
+        session.session_id
+    );
+    Ok(())
+}
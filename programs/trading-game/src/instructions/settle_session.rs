@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, FinalStandings, SessionSettled, TradingSession, SETTLEMENT_WINNERS};
+
+#[derive(Accounts)]
+pub struct SettleSession<'info> {
+    #[account(mut)]
+    pub session: Account<'info, TradingSession>,
+    #[account(mut, seeds = [b"standings", session.key().as_ref()], bump = standings.bump)]
+    pub standings: Account<'info, FinalStandings>,
+}
+
+/// Seal the standings once `settle_portfolios` has added every participant: rank them and mark
+/// the session settled.
+pub fn process_settle_session(ctx: Context<SettleSession>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let standings = &mut ctx.accounts.standings;
+    let clock = Clock::get()?;
+
+    require!(!session.is_settled, ErrorCode::SessionAlreadySettled);
+    require!(
+        standings.entries.len() == session.participant_count as usize,
+        ErrorCode::IncompleteParticipants
+    );
+
+    // stable, so ties keep settlement order
+    standings
+        .entries
+        .sort_by_key(|e| std::cmp::Reverse(e.total_pnl));
+    for (idx, entry) in standings.entries.iter_mut().enumerate() {
+        entry.rank = (idx + 1) as u32;
+    }
+    let winners: Vec<Pubkey> = standings
+        .entries
+        .iter()
+        .take(SETTLEMENT_WINNERS as usize)
+        .map(|e| e.user)
+        .collect();
+    standings.settled_at = clock.unix_timestamp;
+
+    session.is_active = false;
+    session.is_settled = true;
+    session.touch_config()?;
+
+    emit!(SessionSettled {
+        session_id: session.session_id,
+        participant_count: session.participant_count,
+        winners,
+        settled_at: clock.unix_timestamp,
+        version: session.config_version,
+    });
+
+    msg!(
+        "Session {} settled with {} participants",
+        session.session_id,
+        session.participant_count
+    );
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, FinalStandings, TradingSession};
+
+#[derive(Accounts)]
+pub struct StartSettlement<'info> {
+    pub session: Account<'info, TradingSession>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + FinalStandings::space(0),
+        seeds = [b"standings", session.key().as_ref()],
+        bump
+    )]
+    pub standings: Account<'info, FinalStandings>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the empty standings of an ended session. `settle_portfolios` fills them in batches
+/// and `settle_session` seals them.
+pub fn process_start_settlement(ctx: Context<StartSettlement>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let clock = Clock::get()?;
+    require!(clock.unix_timestamp >= session.end_time, ErrorCode::SessionStillActive);
+    require!(!session.is_settled, ErrorCode::SessionAlreadySettled);
+
+    let standings = &mut ctx.accounts.standings;
+    standings.session = session.key();
+    standings.session_id = session.session_id;
+    standings.settled_at = 0;
+    standings.entries = vec![];
+    standings.bump = ctx.bumps.standings;
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::{SessionMirror, SessionMirrorSynced, TradingSession};
+
+#[derive(Accounts)]
+pub struct SyncSessionMirror<'info> {
+    pub session: Account<'info, TradingSession>,
+    #[account(mut, seeds = [b"session_mirror", session.key().as_ref()], bump = session_mirror.bump)]
+    pub session_mirror: Account<'info, SessionMirror>,
+}
+
+/// Permissionless crank: copy the session into its mirror after the authority changed it.
+/// Sent to the rollup while the mirror is delegated, where the session is read as cloned from
+/// the base layer, and to the base layer otherwise.
+pub fn process_sync_session_mirror(ctx: Context<SyncSessionMirror>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let session_mirror = &mut ctx.accounts.session_mirror;
+    if session_mirror.config.config_version == session.config_version {
+        msg!(
+            "Mirror of session {} is already at version {}",
+            session.session_id,
+            session.config_version
+        );
+        return Ok(());
+    }
+
+    session_mirror.config = (**session).clone();
+    session_mirror.synced_at = Clock::get()?.unix_timestamp;
+
+    emit!(SessionMirrorSynced {
+        session_id: session.session_id,
+        config_version: session.config_version,
+    });
+
+    Ok(())
+}
@@ -1,45 +1,36 @@
 use anchor_lang::prelude::*;
 
-use crate::{calculate_roi, Leaderboard, LeaderboardEntry, LeaderboardUpdated, Portfolio};
+use crate::{
+    load_rollup_leaderboard_mut, load_rollup_portfolio, load_rollup_session_mirror,
+    rank_leaderboard, upsert_leaderboard_entry, LeaderboardUpdated,
+};
 #[derive(Accounts)]
 pub struct UpdateLeaderboard<'info> {
+    /// CHECK: loaded in the handler after the delegation check
+    pub session_mirror: UncheckedAccount<'info>,
+    /// CHECK: loaded in the handler after the delegation check
     #[account(mut)]
-    pub leaderboard: Account<'info, Leaderboard>,
-    pub portfolio: Account<'info, Portfolio>,
+    pub leaderboard: UncheckedAccount<'info>,
+    /// CHECK: loaded in the handler after the delegation check
+    pub portfolio: UncheckedAccount<'info>,
 }
 
 
 pub fn process_update_leaderboard(ctx: Context<UpdateLeaderboard>) -> Result<()> {
-        let leaderboard = &mut ctx.accounts.leaderboard;
-        let portfolio = &ctx.accounts.portfolio;
+        let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+        let mut leaderboard = load_rollup_leaderboard_mut(&ctx.accounts.leaderboard, &mirror)?;
+        let portfolio = load_rollup_portfolio(&ctx.accounts.portfolio, &mirror)?;
         let clock = Clock::get()?;
 
-        // find or push
-        if let Some(entry) = leaderboard.entries.iter_mut().find(|e| e.user == portfolio.owner) {
-            entry.total_pnl = portfolio.realized_pnl + portfolio.unrealized_pnl;
-            entry.roi_percentage = calculate_roi(portfolio)?;
-            entry.num_trades = portfolio.num_trades;
-            entry.last_updated = clock.unix_timestamp;
-        } else {
-            leaderboard.entries.push(LeaderboardEntry {
-                user: portfolio.owner,
-                total_pnl: portfolio.realized_pnl + portfolio.unrealized_pnl,
-                roi_percentage: calculate_roi(portfolio)?,
-                num_trades: portfolio.num_trades,
-                last_updated: clock.unix_timestamp,
-                rank: 0,
-            });
-        }
-
-        // sort & rank
-        leaderboard.entries.sort_by(|a, b| b.total_pnl.cmp(&a.total_pnl));
-        for (idx, entry) in leaderboard.entries.iter_mut().enumerate() {
-            entry.rank = (idx + 1) as u32;
-        }
+        upsert_leaderboard_entry(&mut leaderboard, &portfolio, clock.unix_timestamp)?;
+        leaderboard.touch()?;
+        rank_leaderboard(&mut leaderboard);
 
         emit!(LeaderboardUpdated {
             session_id: leaderboard.session_id,
+            num_entries: leaderboard.num_entries,
+            version: leaderboard.version,
         });
 
         Ok(())
-    }
\ No newline at end of file
+    }
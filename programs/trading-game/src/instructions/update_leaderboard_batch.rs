@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    calculate_portfolio_value, error::ErrorCode, load_rollup_leaderboard_mut,
+    load_rollup_session_mirror, load_session_portfolio, rank_leaderboard, require_in_rollup,
+    upsert_leaderboard_entry, LeaderboardUpdated, PnlUpdated,
+};
+
+#[derive(Accounts)]
+pub struct UpdateLeaderboardBatch<'info> {
+    /// CHECK: loaded in the handler after the delegation check
+    pub session_mirror: UncheckedAccount<'info>,
+    /// CHECK: loaded in the handler after the delegation check
+    #[account(mut)]
+    pub leaderboard: UncheckedAccount<'info>,
+}
+
+/// Refresh PnL for a batch of portfolios and re-rank the leaderboard once.
+///
+/// Remaining accounts: the first `portfolio_count` are writable portfolios of this session,
+/// followed by the price update accounts needed to value their positions.
+/// Intended to be cranked inside the ephemeral rollup.
+pub fn process_update_leaderboard_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateLeaderboardBatch<'info>>,
+    portfolio_count: u8,
+) -> Result<()> {
+    let portfolio_count = portfolio_count as usize;
+    require!(
+        portfolio_count > 0 && portfolio_count <= ctx.remaining_accounts.len(),
+        ErrorCode::InvalidBatchSize
+    );
+
+    let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+    let session = &mirror.config;
+    let session_key = mirror.session;
+    let session_id = session.session_id;
+    let mut leaderboard = load_rollup_leaderboard_mut(&ctx.accounts.leaderboard, &mirror)?;
+    let (portfolio_accounts, price_update_accounts) =
+        ctx.remaining_accounts.split_at(portfolio_count);
+    let clock = Clock::get()?;
+
+    for account_info in portfolio_accounts {
+        require!(account_info.is_writable, ErrorCode::PortfolioNotWritable);
+        require_in_rollup(account_info)?;
+        let loader = load_session_portfolio(account_info, &session_key, session_id)?;
+        let mut portfolio = loader.load_mut()?;
+
+        calculate_portfolio_value(&mut portfolio, session, &session_key, price_update_accounts)?;
+        portfolio.touch()?;
+        upsert_leaderboard_entry(&mut leaderboard, &portfolio, clock.unix_timestamp)?;
+
+        emit!(PnlUpdated {
+            session_id,
+            user: portfolio.owner,
+            unrealized_pnl: portfolio.unrealized_pnl,
+            realized_pnl: portfolio.realized_pnl,
+            total_value: portfolio.total_value,
+            cash_balance: portfolio.cash_balance,
+            max_drawdown_bps: portfolio.max_drawdown_bps,
+            mark_time: portfolio.last_mark_time,
+            version: portfolio.version,
+        });
+    }
+
+    leaderboard.touch()?;
+    rank_leaderboard(&mut leaderboard);
+
+    emit!(LeaderboardUpdated {
+        session_id,
+        num_entries: leaderboard.num_entries,
+        version: leaderboard.version,
+    });
+
+    msg!(
+        "Leaderboard for session {} updated from {} portfolios",
+        session_id,
+        portfolio_count
+    );
+    Ok(())
+}
@@ -1,22 +1,39 @@
 use anchor_lang::prelude::*;
 
-use crate::{calculate_portfolio_value, PnlUpdated, Portfolio};
+use crate::{
+    calculate_portfolio_value, load_rollup_portfolio_mut, load_rollup_session_mirror, PnlUpdated,
+};
 #[derive(Accounts)]
 pub struct UpdatePnl<'info> {
+    /// CHECK: loaded in the handler after the delegation check
     #[account(mut)]
-    pub portfolio: Account<'info, Portfolio>,
+    pub portfolio: UncheckedAccount<'info>,
+    /// CHECK: loaded in the handler after the delegation check
+    pub session_mirror: UncheckedAccount<'info>,
 }
 
 
     /// Update P&L (can be called on-chain or executed frequently on ER)
     pub fn process_update_pnl(ctx: Context<UpdatePnl>) -> Result<()> {
-        let portfolio = &mut ctx.accounts.portfolio;
-        calculate_portfolio_value(portfolio, &ctx.remaining_accounts)?;
+        let mirror = load_rollup_session_mirror(&ctx.accounts.session_mirror)?;
+        let mut portfolio = load_rollup_portfolio_mut(&ctx.accounts.portfolio, &mirror)?;
+        calculate_portfolio_value(
+            &mut portfolio,
+            &mirror.config,
+            &mirror.session,
+            ctx.remaining_accounts,
+        )?;
+        portfolio.touch()?;
         emit!(PnlUpdated {
+            session_id: portfolio.session_id,
             user: portfolio.owner,
             unrealized_pnl: portfolio.unrealized_pnl,
             realized_pnl: portfolio.realized_pnl,
             total_value: portfolio.total_value,
+            cash_balance: portfolio.cash_balance,
+            max_drawdown_bps: portfolio.max_drawdown_bps,
+            mark_time: portfolio.last_mark_time,
+            version: portfolio.version,
         });
         Ok(())
     }
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode, FeeConfig, RiskLimits, SessionConfigUpdated, TradingSession,
+};
+
+#[derive(Accounts)]
+pub struct UpdateSessionConfig<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
+    pub session: Account<'info, TradingSession>,
+    pub authority: Signer<'info>,
+}
+
+/// Change the end time, fees or risk limits of a running session; `None` keeps the current
+/// value. The end time may not be moved into the past.
+pub fn process_update_session_config(
+    ctx: Context<UpdateSessionConfig>,
+    end_time: Option<i64>,
+    fees: Option<FeeConfig>,
+    risk_limits: Option<RiskLimits>,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    require!(session.is_active, ErrorCode::SessionInactive);
+    require!(
+        clock.unix_timestamp < session.end_time,
+        ErrorCode::SessionEnded
+    );
+
+    if let Some(end_time) = end_time {
+        require!(end_time > clock.unix_timestamp, ErrorCode::InvalidEndTime);
+        session.end_time = end_time;
+    }
+    if let Some(fees) = fees {
+        fees.validate()?;
+        session.fees = fees;
+    }
+    if let Some(risk_limits) = risk_limits {
+        session.risk_limits = risk_limits;
+    }
+    session.touch_config()?;
+
+    emit!(SessionConfigUpdated {
+        session_id: session.session_id,
+        end_time: session.end_time,
+        fees: session.fees.clone(),
+        risk_limits: session.risk_limits.clone(),
+        config_version: session.config_version,
+    });
+
+    Ok(())
+}
@@ -1,21 +1,16 @@
+#![allow(unexpected_cfgs)]
+#![allow(deprecated)]
+
 pub mod constants;
 pub mod error;
 pub mod instructions;
 pub mod state;
 pub mod event;
 pub mod helpers;
+pub mod fixed_point;
 
 use anchor_lang::prelude::*;
- use ephemeral_rollups_sdk::anchor::{
-    commit,
-    delegate,
-    ephemeral,
-};
-use ephemeral_rollups_sdk::cpi::DelegateConfig;
-use ephemeral_rollups_sdk::ephem::{
-    commit_accounts,
-    commit_and_undelegate_accounts,
-};
+use ephemeral_rollups_sdk::anchor::ephemeral;
 
 
 pub use constants::*;
@@ -23,6 +18,7 @@ pub use instructions::*;
 pub use state::*;
 pub use event::*;
 pub use helpers::*;
+pub use fixed_point::*;
 declare_id!("H6Nh8SuybPujX1jibAn1bbuQhp7cmf7oWmaq4SVjj2cw");
 
 #[ephemeral]
@@ -32,15 +28,54 @@ pub mod trading_game {
     use super::*;
 
 
-    pub fn initialize_session(ctx: Context<InitializeSession>, session_id: u64, duration_seconds: i64, virtual_balance: u64, trading_pairs: Vec<String>) -> Result<()> {
-        process_initialize_session(ctx, session_id, duration_seconds, virtual_balance, trading_pairs)
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_session(ctx: Context<InitializeSession>, session_id: u64, duration_seconds: i64, virtual_balance: u64, trading_pairs: Vec<TradingPair>, fees: FeeConfig, risk_limits: RiskLimits, valuation: ValuationConfig, er_config: ErConfig) -> Result<()> {
+        process_initialize_session(ctx, session_id, duration_seconds, virtual_balance, trading_pairs, fees, risk_limits, valuation, er_config)
+    }
+    pub fn add_trading_pairs(ctx: Context<AddTradingPairs>, new_pairs: Vec<TradingPair>) -> Result<()> {
+        process_add_trading_pairs(ctx, new_pairs)
+    }
+    pub fn update_session_config(ctx: Context<UpdateSessionConfig>, end_time: Option<i64>, fees: Option<FeeConfig>, risk_limits: Option<RiskLimits>) -> Result<()> {
+        process_update_session_config(ctx, end_time, fees, risk_limits)
+    }
+    pub fn set_manual_price(ctx: Context<SetManualPrice>, pair_index: u16, price: i64) -> Result<()> {
+        process_set_manual_price(ctx, pair_index, price)
+    }
+    pub fn set_mock_price(ctx: Context<SetMockPrice>, pair_index: u16, price: i64, publish_time: Option<i64>) -> Result<()> {
+        process_set_mock_price(ctx, pair_index, price, publish_time)
+    }
+    pub fn record_price_sample(ctx: Context<RecordPriceSample>, pair_index: u16) -> Result<()> {
+        process_record_price_sample(ctx, pair_index)
     }
     pub fn join_session(ctx: Context<JoinSession>, session_id: u64) -> Result<()> {
         process_join_session(ctx, session_id)
     }
 
-    pub fn execute_market_order(ctx: Context<ExecuteOrder>, trading_pair: String, side: OrderSide, quantity: u64) -> Result<()>{
-        process_execute_market_order(ctx, trading_pair, side, quantity)
+    pub fn delegate_trading_accounts(ctx: Context<DelegateTradingAccounts>) -> Result<()> {
+        process_delegate_trading_accounts(ctx)
+    }
+
+    pub fn delegate_leaderboard(ctx: Context<DelegateLeaderboard>) -> Result<()> {
+        process_delegate_leaderboard(ctx)
+    }
+
+    pub fn delegate_session_mirror(ctx: Context<DelegateSessionMirror>) -> Result<()> {
+        process_delegate_session_mirror(ctx)
+    }
+    pub fn init_price_guard(ctx: Context<InitPriceGuard>, pair_index: u16) -> Result<()> {
+        process_init_price_guard(ctx, pair_index)
+    }
+    pub fn delegate_price_guard(ctx: Context<DelegatePriceGuard>, pair_index: u16) -> Result<()> {
+        process_delegate_price_guard(ctx, pair_index)
+    }
+    pub fn sync_session_mirror(ctx: Context<SyncSessionMirror>) -> Result<()> {
+        process_sync_session_mirror(ctx)
+    }
+    pub fn execute_market_order(ctx: Context<ExecuteOrder>, pair_index: u16, side: OrderSide, quantity: u64, price_protection: Option<PriceProtection>) -> Result<()>{
+        process_execute_market_order(ctx, pair_index, side, quantity, price_protection)
+    }
+    pub fn execute_market_order_by_amount(ctx: Context<ExecuteOrder>, pair_index: u16, side: OrderSide, amount: OrderAmount, price_protection: Option<PriceProtection>) -> Result<()> {
+        process_execute_market_order_by_amount(ctx, pair_index, side, amount, price_protection)
     }
     pub fn update_pnl(ctx: Context<UpdatePnl>)->Result<()>{
         process_update_pnl(ctx)
@@ -48,13 +83,34 @@ pub mod trading_game {
     pub fn update_leaderboard(ctx: Context<UpdateLeaderboard>)->Result<()>{
         process_update_leaderboard(ctx)
     }
+    pub fn update_leaderboard_batch<'info>(ctx: Context<'_, '_, 'info, 'info, UpdateLeaderboardBatch<'info>>, portfolio_count: u8)->Result<()>{
+        process_update_leaderboard_batch(ctx, portfolio_count)
+    }
     pub fn checkpoint_accounts(ctx: Context<CheckpointAccounts>)->Result<()>{
         process_checkpoint_trading_accounts(ctx)
     }
     pub fn finalize_trading_accounts(ctx: Context<FinalizeTradingAccounts>)->Result<()>{
         process_finalize_and_undelegate(ctx)
     }
+    pub fn fix_settlement_prices(ctx: Context<FixSettlementPrices>, pair_indexes: Vec<u16>) -> Result<()> {
+        process_fix_settlement_prices(ctx, pair_indexes)
+    }
+    pub fn resume_trading(ctx: Context<ResumeTrading>, pair_index: u16) -> Result<()> {
+        process_resume_trading(ctx, pair_index)
+    }
     pub fn close_session(ctx: Context<CloseSession>)->Result<()>{
         process_close_session(ctx)
     }
+    pub fn finalize_session_accounts<'info>(ctx: Context<'_, '_, 'info, 'info, FinalizeSessionAccounts<'info>>, portfolio_count: u8, include_session_mirror: bool) -> Result<()> {
+        process_finalize_session_accounts(ctx, portfolio_count, include_session_mirror)
+    }
+    pub fn start_settlement(ctx: Context<StartSettlement>) -> Result<()> {
+        process_start_settlement(ctx)
+    }
+    pub fn settle_portfolios<'info>(ctx: Context<'_, '_, 'info, 'info, SettlePortfolios<'info>>, portfolio_count: u8) -> Result<()> {
+        process_settle_portfolios(ctx, portfolio_count)
+    }
+    pub fn settle_session(ctx: Context<SettleSession>)->Result<()>{
+        process_settle_session(ctx)
+    }
 }
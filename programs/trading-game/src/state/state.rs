@@ -1,4 +1,10 @@
 use anchor_lang::prelude::*;
+use ephemeral_rollups_sdk::cpi::DelegateConfig;
+
+use crate::{
+    error::ErrorCode, EQUITY_SAMPLE_CAPACITY, MAX_LEADERBOARD_ENTRIES, MAX_POSITIONS,
+    MAX_TAKER_FEE_BPS, MAX_TRADING_PAIRS, TRADE_HISTORY_CAPACITY, TWAP_SAMPLE_CAPACITY,
+};
 
 
 #[account]
@@ -7,16 +13,283 @@ pub struct TradingSession {
     pub start_time: i64,
     pub end_time: i64,
     pub virtual_balance_per_user: u64,
-    pub trading_pairs: Vec<String>,
+    pub trading_pairs: Vec<TradingPair>,
     pub is_active: bool,
     pub participant_count: u32,
+    pub is_settled: bool,
+    pub fees: FeeConfig,
+    pub risk_limits: RiskLimits,
+    pub authority: Pubkey,
+    pub valuation: ValuationConfig,
+    /// Final marks, `settlement_prices[i]` belongs to `trading_pairs[i]`
+    pub settlement_prices: Vec<SettlementPrice>,
+    pub er_config: ErConfig,
+    /// Incremented on every change the session mirror has to pick up
+    pub config_version: u64,
 }
 impl TradingSession {
-    // conservative estimate for space
-    pub const INIT_SPACE: usize = 8 + 8 + 8 + 8 + (4 + 32 * 10) + 1 + 4;
+    /// Account space for a session with `num_pairs` trading pairs
+    pub const fn space(num_pairs: usize) -> usize {
+        8 + 8 + 8 + 8 + (4 + TradingPair::SIZE * num_pairs) + 1 + 4 + 1
+            + FeeConfig::SIZE
+            + RiskLimits::SIZE
+            + 32
+            + ValuationConfig::SIZE
+            + (4 + SettlementPrice::SIZE * num_pairs)
+            + ErConfig::SIZE
+            + 8
+    }
+
+    /// Record a change to the session, so `sync_session_mirror` copies it into the mirror
+    pub fn touch_config(&mut self) -> Result<()> {
+        self.config_version = self
+            .config_version
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Copy of a session that can be delegated to the ephemeral rollup, PDA of
+/// `[b"session_mirror", session]`. Instructions that run in the rollup read the session from
+/// here; the session itself stays on the base layer, where the authority changes it, and
+/// `sync_session_mirror` copies every change across.
+#[account]
+pub struct SessionMirror {
+    pub session: Pubkey,
+    /// Time of the last sync
+    pub synced_at: i64,
+    pub bump: u8,
+    /// The session as of `config.config_version`
+    pub config: TradingSession,
+}
+impl SessionMirror {
+    /// Sized for `MAX_TRADING_PAIRS` up front, so syncs never have to grow a delegated account
+    pub const INIT_SPACE: usize = 32 + 8 + 1 + TradingSession::space(MAX_TRADING_PAIRS);
+}
+
+/// Ephemeral rollup settings applied whenever the session's accounts are delegated
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ErConfig {
+    /// Validator every account of the session is delegated to; `None` lets the delegation
+    /// program pick
+    pub validator: Option<Pubkey>,
+    /// How often the validator commits delegated state to the base layer, in milliseconds;
+    /// 0 uses the SDK default
+    pub commit_frequency_ms: u32,
+    /// Delegate the leaderboard too, so rankings update inside the rollup
+    pub delegate_leaderboard: bool,
+}
+impl ErConfig {
+    const SIZE: usize = (1 + 32) + 4 + 1;
+
+    pub fn delegate_config(&self) -> DelegateConfig {
+        let default = DelegateConfig::default();
+        DelegateConfig {
+            validator: self.validator,
+            commit_frequency_ms: if self.commit_frequency_ms == 0 {
+                default.commit_frequency_ms
+            } else {
+                self.commit_frequency_ms
+            },
+        }
+    }
+}
+
+/// Circuit breaker state of one pair: the last oracle price it traded at, used to detect
+/// abnormal moves. PDA of `[b"price_guard", session, pair_index]`, one per pair and zero-copy,
+/// so a fill only locks the guard of the pair it trades.
+#[account(zero_copy)]
+pub struct PriceGuard {
+    pub session: Pubkey,
+    /// Price in the 6-decimal quote format; 0 until the first fill or after a resume
+    pub last_price: i64,
+    /// Publish time of `last_price`; the reference is ignored once older than `PRICE_GUARD_MAX_AGE`
+    pub last_publish_time: i64,
+    /// Incremented whenever the pair is halted or resumed
+    pub version: u64,
+    pub pair_index: u16,
+    /// Non-zero when a move exceeded `max_price_move_bps`; cleared by `resume_trading`
+    pub halted: u8,
+    pub bump: u8,
+    pub _padding: [u8; 4],
+}
+impl PriceGuard {
+    pub const INIT_SPACE: usize = std::mem::size_of::<PriceGuard>();
+
+    pub fn is_halted(&self) -> bool {
+        self.halted != 0
+    }
+
+    /// Record a halt or resume, so circuit breaker events can be ordered
+    pub fn touch(&mut self) -> Result<()> {
+        self.version = self.version.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// Price a pair is settled at, fixed once by `fix_settlement_prices`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct SettlementPrice {
+    /// Price in the 6-decimal quote format; 0 until fixed
+    pub price: i64,
+    pub publish_time: i64,
+}
+impl SettlementPrice {
+    const SIZE: usize = 8 + 8;
+
+    pub fn is_fixed(&self) -> bool {
+        self.price > 0
+    }
+}
+
+/// Price portfolios are marked at
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum PriceMode {
+    /// Latest oracle price
+    #[default]
+    Spot,
+    /// Pyth's exponentially-weighted moving average; spot for other sources
+    Ema,
+    /// Time-weighted average of the pair's `PriceTwap` samples over `TWAP_WINDOW`
+    Twap,
+}
+
+/// Which price marks portfolios while the session runs and which one fixes settlement prices
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ValuationConfig {
+    pub mark: PriceMode,
+    pub settlement: PriceMode,
+}
+impl ValuationConfig {
+    const SIZE: usize = 1 + 1;
+}
+
+/// Per-player risk limits enforced on position-increasing orders; 0 disables a limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct RiskLimits {
+    /// Largest notional (6 decimals) a single pair's position may reach
+    pub max_position_notional: u64,
+    /// Most pairs a player may hold at once, never more than `MAX_POSITIONS`
+    pub max_open_positions: u8,
+    /// Largest marked value (6 decimals) of all positions combined
+    pub max_gross_exposure: u64,
+}
+impl RiskLimits {
+    const SIZE: usize = 8 + 1 + 8;
+}
+
+/// Market specification for one tradable pair.
+/// Quantities are in base units of `10^-base_decimals`, prices and notionals use 6 decimals.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TradingPair {
+    pub symbol: String,
+    pub base_decimals: u8,
+    /// Order quantities must be a multiple of this many base units
+    pub lot_size: u64,
+    /// Smallest accepted order notional
+    pub min_notional: u64,
+    /// Where the pair's price is read from
+    pub oracle: OracleSource,
+    /// Largest move from the last traded price, in basis points, before trading on the pair
+    /// halts; 0 disables the circuit breaker
+    pub max_price_move_bps: u16,
+}
+impl TradingPair {
+    const SIZE: usize = (4 + 32) + 1 + 8 + 8 + OracleSource::SIZE + 2;
+
+    /// Number of base units in one whole unit of the asset
+    pub fn base_unit(&self) -> i64 {
+        10_i64.pow(self.base_decimals as u32)
+    }
 }
 
+/// Price source of a trading pair
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum OracleSource {
+    /// Pyth pull oracle: any `PriceUpdateV2` account posted for `feed_id`
+    PythPull { feed_id: [u8; 32] },
+    /// Pyth push oracle: the sponsored price feed PDA for `feed_id` on `shard_id`
+    PythPush { feed_id: [u8; 32], shard_id: u16 },
+    /// Switchboard on-demand pull feed account
+    Switchboard { feed: Pubkey },
+    /// Price (6 decimals) set by the session authority with `set_manual_price`; goes stale like
+    /// a feed once `updated_at` is older than the reader allows
+    Manual { price: i64, updated_at: i64 },
+    /// `MockPriceFeed` account scripted with `set_mock_price`; needs the `mock-oracle` feature
+    Mock { feed: Pubkey },
+}
+impl OracleSource {
+    const SIZE: usize = 1 + 32 + 2;
+}
+
+/// Recent oracle prices of one pair, PDA of `[b"twap", session, pair_index]`, kept by the
+/// permissionless `record_price_sample` crank
 #[account]
+pub struct PriceTwap {
+    pub session: Pubkey,
+    pub pair_index: u16,
+    /// Index of the oldest sample, i.e. the next slot overwritten once full
+    pub head: u16,
+    pub samples: Vec<PriceSample>,
+    pub bump: u8,
+}
+impl PriceTwap {
+    pub const INIT_SPACE: usize = 32 + 2 + 2 + (4 + PriceSample::SIZE * TWAP_SAMPLE_CAPACITY) + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PriceSample {
+    /// Price in the 6-decimal quote format
+    pub price: i64,
+    pub publish_time: i64,
+}
+impl PriceSample {
+    const SIZE: usize = 8 + 8;
+}
+
+/// Authority-controlled price for one pair, PDA of `[b"mock_price", session, pair_index]`
+#[account]
+pub struct MockPriceFeed {
+    pub session: Pubkey,
+    pub pair_index: u16,
+    /// Price in the 6-decimal quote format
+    pub price: i64,
+    pub publish_time: i64,
+    pub bump: u8,
+}
+impl MockPriceFeed {
+    pub const INIT_SPACE: usize = 32 + 2 + 8 + 8 + 1;
+}
+
+/// Trading costs charged on every fill
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct FeeConfig {
+    /// Fee charged on filled notional, in basis points
+    pub taker_fee_bps: u16,
+    /// Slippage added per `slippage_step_notional` of order notional, in basis points
+    pub slippage_bps_per_step: u16,
+    /// Notional (6 decimals) per slippage step; 0 disables slippage
+    pub slippage_step_notional: u64,
+    /// Upper bound on slippage, in basis points
+    pub max_slippage_bps: u16,
+}
+impl FeeConfig {
+    const SIZE: usize = 2 + 2 + 8 + 2;
+
+    /// Fees are capped at `MAX_TAKER_FEE_BPS` and slippage must stay below 100%
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.taker_fee_bps <= MAX_TAKER_FEE_BPS && self.max_slippage_bps < 10_000,
+            ErrorCode::InvalidFeeConfig
+        );
+        Ok(())
+    }
+}
+
+/// Zero-copy so orders and PnL updates in the ephemeral rollup touch the account in place
+/// instead of deserializing and reserializing it.
+#[account(zero_copy)]
 pub struct Portfolio {
     pub owner: Pubkey,
     pub session_id: u64,
@@ -24,38 +297,162 @@ pub struct Portfolio {
     pub total_value: i64,
     pub realized_pnl: i64,
     pub unrealized_pnl: i64,
+    /// Cumulative taker fees charged on fills
+    pub fees_paid: u64,
+    /// Virtual balance the portfolio started the session with
+    pub initial_balance: u64,
+    /// Oldest oracle publish time used in the last valuation
+    pub last_mark_time: i64,
+    /// Highest `total_value` observed so far
+    pub high_water_mark: i64,
+    /// Incremented on every change to the portfolio or its trade history
+    pub version: u64,
+    /// `version` as of the last commit to the base layer
+    pub committed_version: u64,
     pub num_trades: u32,
-    pub positions: Vec<Position>,
+    /// Largest peak-to-trough decline of `total_value`, in basis points of the peak
+    pub max_drawdown_bps: u32,
+    /// Index of the oldest equity sample, i.e. the next slot overwritten once full
+    pub equity_head: u16,
+    /// Number of filled slots in `equity_samples`
+    pub equity_count: u16,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    /// Indexed by the pair's position in `TradingSession::trading_pairs`; zero quantity means flat
+    pub positions: [Position; MAX_POSITIONS],
+    pub equity_samples: [EquitySample; EQUITY_SAMPLE_CAPACITY],
 }
 impl Portfolio {
-    pub const INIT_SPACE: usize = 32 + 8 + 8 + 8 + 8 + 8 + 4 + (4 + Position::SIZE * 20);
+    pub const INIT_SPACE: usize = std::mem::size_of::<Portfolio>();
+
+    pub fn open_positions(&self) -> usize {
+        self.positions.iter().filter(|p| p.quantity > 0).count()
+    }
+
+    /// Record a change, so the next checkpoint commits the portfolio
+    pub fn touch(&mut self) -> Result<()> {
+        self.version = self.version.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Whether the portfolio changed since it was last committed
+    pub fn has_uncommitted_changes(&self) -> bool {
+        self.version != self.committed_version
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// Long position in one pair
+#[zero_copy]
 pub struct Position {
-    pub trading_pair: String,
     pub quantity: u64,
     pub avg_entry_price: i64,
-    pub side: PositionSide,
 }
-impl Position {
-    const SIZE: usize = 32 + 8 + 8 + 1;
+
+#[zero_copy]
+pub struct EquitySample {
+    pub timestamp: i64,
+    pub total_value: i64,
 }
 
-#[account]
+#[account(zero_copy)]
 pub struct Leaderboard {
     pub session_id: u64,
-    pub entries: Vec<LeaderboardEntry>,
+    /// Incremented on every change to the entries
+    pub version: u64,
+    /// `version` as of the last commit to the base layer
+    pub committed_version: u64,
+    /// Number of filled slots in `entries`, kept sorted by rank
+    pub num_entries: u32,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub entries: [LeaderboardEntry; MAX_LEADERBOARD_ENTRIES],
 }
+impl Leaderboard {
+    pub const INIT_SPACE: usize = std::mem::size_of::<Leaderboard>();
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+    /// Record a change, so the next checkpoint commits the leaderboard
+    pub fn touch(&mut self) -> Result<()> {
+        self.version = self.version.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Whether the leaderboard changed since it was last committed
+    pub fn has_uncommitted_changes(&self) -> bool {
+        self.version != self.committed_version
+    }
+}
+
+#[zero_copy]
 pub struct LeaderboardEntry {
     pub user: Pubkey,
     pub total_pnl: i64,
     pub roi_percentage: f64,
-    pub num_trades: u32,
     pub last_updated: i64,
+    pub num_trades: u32,
+    pub rank: u32,
+    pub max_drawdown_bps: u32,
+    pub _padding: [u8; 4],
+}
+
+/// Fixed-size ring buffer of recent fills, companion PDA of a portfolio
+#[account]
+pub struct TradeHistory {
+    pub portfolio: Pubkey,
+    /// Index of the oldest record, i.e. the next slot overwritten once the buffer is full
+    pub head: u16,
+    /// Fills recorded over the lifetime of the portfolio, including overwritten ones
+    pub total_recorded: u64,
+    pub records: Vec<TradeRecord>,
+    pub bump: u8,
+}
+impl TradeHistory {
+    pub const INIT_SPACE: usize = 32 + 2 + 8 + (4 + TradeRecord::SIZE * TRADE_HISTORY_CAPACITY) + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TradeRecord {
+    /// Index into the session's `trading_pairs`
+    pub pair_index: u16,
+    pub side: OrderSide,
+    pub quantity: u64,
+    pub price: i64,
+    pub timestamp: i64,
+    /// Realized PnL of the fill, net of `fee`
+    pub realized_pnl: i64,
+    pub fee: u64,
+}
+impl TradeRecord {
+    const SIZE: usize = 2 + 1 + 8 + 8 + 8 + 8 + 8;
+}
+
+/// Final results, filled in batches by `settle_portfolios` and sealed by `settle_session`;
+/// `settled_at` stays 0 and ranks are unassigned until then
+#[account]
+pub struct FinalStandings {
+    pub session: Pubkey,
+    pub session_id: u64,
+    pub settled_at: i64,
+    pub entries: Vec<StandingEntry>,
+    pub bump: u8,
+}
+impl FinalStandings {
+    pub fn space(participant_count: usize) -> usize {
+        32 + 8 + 8 + (4 + StandingEntry::SIZE * participant_count) + 1
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StandingEntry {
+    pub user: Pubkey,
+    pub total_value: i64,
+    pub total_pnl: i64,
+    pub roi_percentage: f64,
+    pub num_trades: u32,
     pub rank: u32,
+    pub max_drawdown_bps: u32,
+}
+impl StandingEntry {
+    const SIZE: usize = 32 + 8 + 8 + 8 + 4 + 4 + 4;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
@@ -64,8 +461,27 @@ pub enum OrderSide {
     Sell,
 }
 
+/// How the size of a market order is specified
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum OrderAmount {
+    /// Base quantity in the pair's smallest unit
+    Quantity(u64),
+    /// Quote notional (6 decimals): total spend including fees on a buy, gross proceeds on a sell
+    Notional(u64),
+    /// Share of the cash balance to spend, in basis points (buys only)
+    PercentOfCash(u16),
+    /// Sell the entire position in the pair (sells only)
+    ClosePosition,
+}
+
+/// Worst fill price a market order accepts, in the 6-decimal quote format
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
-pub enum PositionSide {
-    Long,
-    Short,
+pub enum PriceProtection {
+    /// Maximum price for buys, minimum price for sells
+    Limit(i64),
+    /// Allowed deviation from the price the client quoted to the player
+    Tolerance {
+        reference_price: i64,
+        max_deviation_bps: u16,
+    },
 }
@@ -1,16 +1,176 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { assert } from "chai";
 import { TradingGame } from "../target/types/trading_game";
 
-describe("trading-game", () => {
-  // Configure the client to use the local cluster.
+// Prices are scripted through mock feeds, so the program must be built with the
+// `mock-oracle` feature: `anchor test -- --features mock-oracle`.
+describe("trading-game with scripted mock prices", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
 
   const program = anchor.workspace.tradingGame as Program<TradingGame>;
+  const provider = program.provider as anchor.AnchorProvider;
+  const authority = provider.wallet.publicKey;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const sessionId = new BN(Date.now());
+  const session = Keypair.generate();
+  const pairIndex = 0;
+
+  // 6-decimal quote amounts: 1_000_000 = 1.0 USD
+  const usd = (amount: number) => new BN(amount).mul(new BN(1_000_000));
+  const oneSol = new BN(1_000_000_000);
+  const virtualBalance = usd(10_000);
+  const takerFeeBps = 10;
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const pairIndexSeed = Buffer.alloc(2);
+  pairIndexSeed.writeUInt16LE(pairIndex);
+
+  const leaderboard = pda(Buffer.from("leaderboard"), session.publicKey.toBuffer());
+  const sessionMirror = pda(Buffer.from("session_mirror"), session.publicKey.toBuffer());
+  const mockPriceFeed = pda(
+    Buffer.from("mock_price"),
+    session.publicKey.toBuffer(),
+    pairIndexSeed
+  );
+  const priceGuard = pda(
+    Buffer.from("price_guard"),
+    session.publicKey.toBuffer(),
+    pairIndexSeed
+  );
+  const portfolio = pda(
+    Buffer.from("portfolio"),
+    session.publicKey.toBuffer(),
+    authority.toBuffer()
+  );
+  const tradeHistory = pda(Buffer.from("trade_history"), portfolio.toBuffer());
+  const priceAccounts = [{ pubkey: mockPriceFeed, isSigner: false, isWritable: false }];
+
+  const setPrice = (price: BN, publishTime: BN | null = null) =>
+    program.methods
+      .setMockPrice(pairIndex, price, publishTime)
+      .accountsPartial({
+        session: session.publicKey,
+        mockPriceFeed,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+  const buy = (quantity: BN) =>
+    program.methods
+      .executeMarketOrder(pairIndex, { buy: {} }, quantity, null)
+      .accountsPartial({
+        portfolio,
+        tradeHistory,
+        sessionMirror,
+        priceGuard,
+        priceFeed: mockPriceFeed,
+        user: authority,
+      })
+      .remainingAccounts(priceAccounts)
+      .rpc();
+
+  before(async () => {
+    await program.methods
+      .initializeSession(
+        sessionId,
+        new BN(3600),
+        virtualBalance,
+        [
+          {
+            symbol: "SOL/USD",
+            baseDecimals: 9,
+            lotSize: new BN(1_000_000),
+            minNotional: usd(1),
+            oracle: { mock: { feed: mockPriceFeed } },
+            maxPriceMoveBps: 0,
+          },
+        ],
+        {
+          takerFeeBps,
+          slippageBpsPerStep: 0,
+          slippageStepNotional: new BN(0),
+          maxSlippageBps: 0,
+        },
+        {
+          maxPositionNotional: new BN(0),
+          maxOpenPositions: 0,
+          maxGrossExposure: new BN(0),
+        },
+        { mark: { spot: {} }, settlement: { spot: {} } },
+        { validator: null, commitFrequencyMs: 0, delegateLeaderboard: false }
+      )
+      .accountsPartial({
+        session: session.publicKey,
+        leaderboard,
+        sessionMirror,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([session])
+      .rpc();
+
+    await program.methods
+      .initPriceGuard(pairIndex)
+      .accountsPartial({
+        session: session.publicKey,
+        priceGuard,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .joinSession(sessionId)
+      .accountsPartial({
+        session: session.publicKey,
+        portfolio,
+        tradeHistory,
+        user: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("fills a buy at the scripted price", async () => {
+    await setPrice(usd(150));
+    await buy(oneSol);
+
+    const state = await program.account.portfolio.fetch(portfolio);
+    const fee = usd(150).muln(takerFeeBps).divn(10_000);
+    assert.ok(state.positions[pairIndex].quantity.eq(oneSol));
+    assert.ok(state.positions[pairIndex].avgEntryPrice.eq(usd(150)));
+    assert.ok(state.cashBalance.eq(virtualBalance.sub(usd(150)).sub(fee)));
+    assert.ok(state.realizedPnl.eq(fee.neg()));
+  });
+
+  it("marks the position at the next scripted price", async () => {
+    await setPrice(usd(165));
+    await program.methods
+      .updatePnl()
+      .accountsPartial({ portfolio, sessionMirror })
+      .remainingAccounts(priceAccounts)
+      .rpc();
+
+    const state = await program.account.portfolio.fetch(portfolio);
+    assert.ok(state.unrealizedPnl.eq(usd(15)));
+    assert.ok(state.totalValue.eq(state.cashBalance.add(usd(165))));
+  });
+
+  it("rejects orders against a stale scripted price", async () => {
+    const hourAgo = new BN(Math.floor(Date.now() / 1000) - 3600);
+    await setPrice(usd(170), hourAgo);
+    try {
+      await buy(oneSol);
+      assert.fail("order filled against a stale price");
+    } catch (err) {
+      assert.equal(
+        (err as anchor.AnchorError).error.errorCode.code,
+        "StalePriceData"
+      );
+    }
   });
 });